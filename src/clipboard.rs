@@ -0,0 +1,25 @@
+///
+/// Clipboard Module
+///
+/// Copies text to the system clipboard. On X11 the text is only there for
+/// as long as the clipboard that set it is open, so it's kept open until
+/// the program exits.
+use std::sync::{Mutex, OnceLock};
+
+use arboard::Clipboard;
+
+static CLIPBOARD: OnceLock<Mutex<Option<Clipboard>>> = OnceLock::new();
+
+/// copy
+/// Puts `text` on the clipboard, or explains why there isn't one to put it
+/// on, e.g. over SSH.
+pub fn copy(text: &str) -> Result<(), String> {
+    let mut clipboard = CLIPBOARD.get_or_init(|| Mutex::new(None)).lock().unwrap_or_else(|e| e.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    match clipboard.as_mut() {
+        Some(clipboard) => clipboard.set_text(text).map_err(|e| e.to_string()),
+        None => Err("no clipboard".to_string()),
+    }
+}
@@ -0,0 +1,128 @@
+///
+/// Config Module
+///
+/// Defaults for the command line, read from `mazeme/config.toml` in the
+/// config directory. Every key is optional and named after its flag:
+///
+/// ```text
+/// width = 41
+/// height = 21
+/// algo = "kruskal"
+/// theme = "high-contrast"
+/// goal-glyph = "🏁"
+/// quiet = true
+///
+/// [keys]
+/// up = "k"
+/// down = "j"
+/// ```
+///
+/// Anything given on the command line wins over the file.
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::game_state::gen::{Difficulty, MazeAlgorithm};
+use crate::input::KeyBindings;
+use crate::render::{Theme, WallStyle};
+
+/// Config
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Board size in cells, either one defaults to the other.
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    #[serde(deserialize_with = "parsed")]
+    pub algo: Option<MazeAlgorithm>,
+    #[serde(deserialize_with = "parsed")]
+    pub difficulty: Option<Difficulty>,
+    pub braid: Option<f64>,
+    #[serde(deserialize_with = "parsed")]
+    pub theme: Option<Theme>,
+    #[serde(deserialize_with = "parsed")]
+    pub walls: Option<WallStyle>,
+    pub fog: Option<usize>,
+    pub player_glyph: Option<String>,
+    pub goal_glyph: Option<String>,
+    pub repeat_ms: Option<u64>,
+    pub compact: bool,
+    pub half_blocks: bool,
+    pub no_color: bool,
+    pub no_help: bool,
+    pub quiet: bool,
+    pub stats_json: bool,
+    /// Keys left out keep their usual binding.
+    pub keys: Option<KeyBindings>,
+}
+
+impl Config {
+    /// default_path
+    /// `mazeme/config.toml` in the config directory, see `config_dir`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(config_dir()?.join("config.toml"))
+    }
+
+    /// load
+    /// Reads the config at `path`, a missing file is the default config.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// parse
+    /// Reads a config from TOML, explaining the first thing wrong with it.
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        if config.braid.is_some_and(|f| !(0.0..=1.0).contains(&f)) {
+            return Err("braid must be between 0.0 and 1.0".to_string());
+        }
+        if config.width == Some(0) || config.height == Some(0) {
+            return Err("width and height must be at least 1".to_string());
+        }
+        let glyphs = [&config.player_glyph, &config.goal_glyph];
+        if glyphs.into_iter().flatten().any(|g| g.trim().is_empty() || g.chars().any(char::is_control)) {
+            return Err("player-glyph and goal-glyph must be visible".to_string());
+        }
+        if let Some(key) = config.keys.as_ref().and_then(KeyBindings::clash) {
+            return Err(format!("key {key:?} is bound to more than one action in [keys]"));
+        }
+        Ok(config)
+    }
+
+    /// size
+    /// The board size, if either dimension is set.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        match (self.width, self.height) {
+            (Some(w), Some(h)) => Some((w, h)),
+            (Some(n), None) | (None, Some(n)) => Some((n, n)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// config_dir
+/// `mazeme` in `$XDG_CONFIG_HOME`, or `~/.config` if unset. None if
+/// neither can be found.
+pub fn config_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()).map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").filter(|v| !v.is_empty()).map(|h| PathBuf::from(h).join(".config")))?;
+    Some(config.join("mazeme"))
+}
+
+/// parsed
+/// Reads a string the way the matching flag does.
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map(Some).map_err(serde::de::Error::custom)
+}
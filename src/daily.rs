@@ -0,0 +1,52 @@
+///
+/// Daily Challenge Module
+///
+/// Everyone playing on the same UTC day gets the same maze, seeded by the
+/// date as the number `YYYYMMDD`.
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Date
+/// A day in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u64,
+    pub month: u64,
+    pub day: u64,
+}
+
+impl Date {
+    /// today
+    /// The current date in UTC.
+    pub fn today() -> Date {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Date::from_days(secs / 86_400)
+    }
+
+    /// from_days
+    /// The date `days` after 1970-01-01, by Howard Hinnant's
+    /// `civil_from_days`.
+    pub fn from_days(days: u64) -> Date {
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        Date { year: yoe + era * 400 + u64::from(month <= 2), month, day }
+    }
+
+    /// seed
+    /// The date as `YYYYMMDD`.
+    pub fn seed(&self) -> u64 {
+        self.year * 10_000 + self.month * 100 + self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
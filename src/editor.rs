@@ -0,0 +1,144 @@
+///
+/// Maze Editor Module
+///
+/// Walls are drawn by hand on a blank board, or on one loaded from a text
+/// file, which is also how the design is saved, see `export_ascii`.
+use console::Key;
+
+use crate::game_state::{GameState, Movement, Position, GOAL, SYMBOL, WALL};
+use crate::render::{export_ascii, import_ascii};
+
+const ESC: &str = "\x1b";
+
+/// EditorAction
+/// What the caller should do after a key press.
+pub enum EditorAction {
+    Continue,
+    /// Write the design, as text, to the file being edited.
+    Save(String),
+    /// Play the design, which has been checked to be solvable.
+    Play(Box<GameState>),
+    Quit,
+}
+
+/// Editor
+/// The arrow keys move the cursor, space toggles a wall, `s` and `g` put
+/// the start and goal under it, `o` saves, Enter plays and q or Escape
+/// quits.
+pub struct Editor {
+    board: Vec<Vec<char>>,
+    cursor: Position,
+    /// Why the last save or play didn't happen, or that a save did.
+    pub message: Option<String>,
+}
+
+impl Editor {
+    /// new
+    /// A `width` x `height` render board with no walls.
+    pub fn new(width: usize, height: usize) -> Editor {
+        Editor::from_board(vec![vec![' '; width.max(1)]; height.max(1)])
+    }
+
+    /// from_state
+    /// Edits the board of an existing game, its start and goal included.
+    pub fn from_state(state: &GameState) -> Editor {
+        Editor::from_board(state.board().to_vec())
+    }
+
+    fn from_board(board: Vec<Vec<char>>) -> Editor {
+        Editor { board, cursor: Position::new(0, 0), message: None }
+    }
+
+    /// board
+    pub fn board(&self) -> &[Vec<char>] {
+        &self.board
+    }
+
+    /// cursor
+    pub fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// handle_key
+    pub fn handle_key(&mut self, key: &Key) -> EditorAction {
+        self.message = None;
+        match key {
+            Key::ArrowUp => self.move_cursor(Movement::UP),
+            Key::ArrowLeft => self.move_cursor(Movement::LEFT),
+            Key::ArrowDown => self.move_cursor(Movement::DOWN),
+            Key::ArrowRight => self.move_cursor(Movement::RIGHT),
+            Key::Char(' ') => self.toggle_wall(),
+            Key::Char('s') => self.place(SYMBOL),
+            Key::Char('g') => self.place(GOAL),
+            Key::Char('o') => return EditorAction::Save(export_ascii(&self.board)),
+            Key::Enter => match import_ascii(&export_ascii(&self.board)) {
+                Ok(state) => return EditorAction::Play(Box::new(state)),
+                Err(e) => self.message = Some(format!("Can't play yet: {e}")),
+            },
+            Key::Escape | Key::Char('q') => return EditorAction::Quit,
+            _ => (),
+        }
+        EditorAction::Continue
+    }
+
+    /// move_cursor
+    /// Steps the cursor, stopping at the board edges.
+    pub fn move_cursor(&mut self, action: Movement) {
+        let (x, y) = (self.cursor.x(), self.cursor.y());
+        let (width, height) = (self.board[0].len(), self.board.len());
+        self.cursor = match action {
+            Movement::UP => Position::new(x, y.saturating_sub(1)),
+            Movement::LEFT => Position::new(x.saturating_sub(1), y),
+            Movement::DOWN => Position::new(x, (y + 1).min(height - 1)),
+            Movement::RIGHT => Position::new((x + 1).min(width - 1), y),
+            Movement::UP_LEFT | Movement::UP_RIGHT | Movement::DOWN_LEFT | Movement::DOWN_RIGHT => self.cursor,
+        };
+    }
+
+    /// toggle_wall
+    /// Builds a wall under the cursor, over the start or goal if need be,
+    /// or knocks one down.
+    pub fn toggle_wall(&mut self) {
+        let cell = &mut self.board[self.cursor.y()][self.cursor.x()];
+        *cell = if *cell == WALL { ' ' } else { WALL };
+    }
+
+    /// place
+    /// Moves the start (`SYMBOL`) or goal (`GOAL`) under the cursor.
+    pub fn place(&mut self, glyph: char) {
+        for cell in self.board.iter_mut().flatten().filter(|cell| **cell == glyph) {
+            *cell = ' ';
+        }
+        self.board[self.cursor.y()][self.cursor.x()] = glyph;
+    }
+
+    /// draw
+    /// The board centered on a `wd` x `ht` screen with the cursor cell
+    /// reversed, as one frame of escape codes.
+    pub fn draw(&self, (wd, ht): (usize, usize)) -> String {
+        let (width, height) = (self.board[0].len(), self.board.len());
+        let top = ht.saturating_sub(height + 3) / 2 + 1;
+        let left = wd.saturating_sub(3 * width) / 2 + 1;
+        let mut frame = format!("{ESC}[2J");
+        for (y, row) in self.board.iter().enumerate() {
+            frame.push_str(format!("{ESC}[{row};{left}H", row = top + y).as_str());
+            for (x, &v) in row.iter().enumerate() {
+                let glyph = match v {
+                    SYMBOL => "◀◆▶".to_string(),
+                    ' ' => " · ".to_string(),
+                    v => format!("{v}{v}{v}"),
+                };
+                match Position::new(x, y) == self.cursor {
+                    true => frame.push_str(format!("{ESC}[7m{glyph}{ESC}[0m").as_str()),
+                    false => frame.push_str(&glyph),
+                }
+            }
+        }
+        let help = "←↑↓→ move  space wall  s start  g goal  o save  Enter play  q quit";
+        frame.push_str(format!("{ESC}[{row};{left}H{ESC}[2m{help}{ESC}[0m", row = top + height + 1).as_str());
+        if let Some(message) = &self.message {
+            frame.push_str(format!("{ESC}[{row};{left}H{message}", row = top + height + 2).as_str());
+        }
+        frame
+    }
+}
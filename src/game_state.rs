@@ -1,112 +1,1107 @@
 ///
 /// GameState
 ///
-mod gen;
+pub mod gen;
+#[cfg(feature = "serde")]
+pub mod net;
+#[cfg(feature = "serde")]
+pub mod replay;
+#[cfg(feature = "serde")]
+pub mod save;
+#[cfg(feature = "serde")]
+pub mod scores;
+pub mod solve;
+pub mod stats;
+#[cfg(feature = "serde")]
+pub mod summary;
 
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
 use std::sync::mpsc::{Receiver, Sender, self};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use self::gen::generate_maze;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-const WALL: char = '░';
+use self::gen::{
+    braid, convert_wrapped_maze, generate_maze, generate_maze_sized, generate_maze_steps, generate_maze_torus,
+    generate_maze_with, generate_maze_with_rng, Difficulty, MazeAlgorithm,
+};
+use self::stats::{analyze, analyze_wrapping, HeatMap, MazeStats};
+use self::solve::{
+    manhattan, neighbors, path_to_movements, path_to_wrapping_movements, solve_astar, solve_nearest,
+    solve_nearest_through, solve_nearest_wrapping, solve_weighted, wrapping_neighbors,
+};
+use crate::clipboard;
+use crate::render::{export_ascii, CELL_WIDTHS};
+
+pub const WALL: char = '░';
 pub const SYMBOL: char = '●';
 pub const GOAL: char = '▓';
+const ITEM_POINTS: [u32; 3] = [10, 25, 50];
+/// How often the world is updated without player input.
+pub const TICK: Duration = Duration::from_millis(100);
+const TICKS_PER_SECOND: u64 = 10;
+const TICKS_PER_ENEMY_STEP: u64 = 4;
+const SOLVE_STEP_DELAY: Duration = Duration::from_millis(60);
+const HINT_TICKS: u64 = 15;
+/// The most a mud cell can cost to enter, the least is 2.
+const MAX_MUD_COST: u32 = 4;
+/// The longest the fog can wait between closing in, in seconds.
+pub const MAX_FOG_CLOSING: u64 = 3600;
+/// How long "GO!" stays up once a countdown ends.
+const GO_TICKS: u64 = 5;
 
 pub enum StateEvent {
     Movement(Movement),
+    /// A move by one player of a race, 0 being the same as `Movement`.
+    PlayerMovement(usize, Movement),
+    /// Where the rival playing over the network moved to.
+    RivalMoved(Position),
+    RivalWon,
+    RivalLeft,
     Clock(Clock),
+    Solve,
+    Hint,
+    ToggleTrail,
+    ToggleSolution,
+    ToggleHeatMap,
+    Undo,
+    Pause,
+    Tick,
+    #[cfg(feature = "serde")]
+    Save(PathBuf),
+    /// Copy the maze to the clipboard as `export_ascii` text.
+    Copy,
+    /// Start the same maze over, see `GameState::restart`.
+    Restart,
+    /// Draw cells the next width narrower, or back to the widest.
+    Zoom,
+    /// Stop listening, the game is over for good.
+    Quit,
     NoOP,
 }
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub enum Clock {
     ADD(u32),
     SUB(u32),
     SET(u32),
 }
 
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Movement {
     UP,
     DOWN,
     LEFT,
     RIGHT,
+    /// Diagonals only move anything in games with `diagonals` on.
+    UP_LEFT,
+    UP_RIGHT,
+    DOWN_LEFT,
+    DOWN_RIGHT,
+}
+
+impl Movement {
+    /// The four straight moves, then the diagonals.
+    pub const ALL: [Movement; 8] = [
+        Movement::UP,
+        Movement::LEFT,
+        Movement::DOWN,
+        Movement::RIGHT,
+        Movement::UP_LEFT,
+        Movement::UP_RIGHT,
+        Movement::DOWN_LEFT,
+        Movement::DOWN_RIGHT,
+    ];
+
+    /// parts
+    /// The vertical and horizontal steps a diagonal is made of, None for
+    /// the straight moves.
+    pub fn parts(self) -> Option<(Movement, Movement)> {
+        match self {
+            Movement::UP_LEFT => Some((Movement::UP, Movement::LEFT)),
+            Movement::UP_RIGHT => Some((Movement::UP, Movement::RIGHT)),
+            Movement::DOWN_LEFT => Some((Movement::DOWN, Movement::LEFT)),
+            Movement::DOWN_RIGHT => Some((Movement::DOWN, Movement::RIGHT)),
+            _ => None,
+        }
+    }
+
+    /// between
+    /// The single step that moves from `from` to the adjacent `to`.
+    pub fn between(from: Position, to: Position) -> Option<Movement> {
+        match (to.x as isize - from.x as isize, to.y as isize - from.y as isize) {
+            (0, -1) => Some(Movement::UP),
+            (0, 1) => Some(Movement::DOWN),
+            (-1, 0) => Some(Movement::LEFT),
+            (1, 0) => Some(Movement::RIGHT),
+            (-1, -1) => Some(Movement::UP_LEFT),
+            (1, -1) => Some(Movement::UP_RIGHT),
+            (-1, 1) => Some(Movement::DOWN_LEFT),
+            (1, 1) => Some(Movement::DOWN_RIGHT),
+            _ => None,
+        }
+    }
+
+    /// between_wrapping
+    /// Like `between`, on a `width` x `height` board whose edges wrap, so
+    /// stepping from the last column to the first is a move RIGHT.
+    pub fn between_wrapping(from: Position, to: Position, (width, height): (usize, usize)) -> Option<Movement> {
+        let wrapped = |a: usize, b: usize, len: usize| match (a, b) {
+            (a, 0) if len > 2 && a == len - 1 => Some(1),
+            (0, b) if len > 2 && b == len - 1 => Some(-1),
+            _ => None,
+        };
+        match (wrapped(from.x, to.x, width), wrapped(from.y, to.y, height)) {
+            (Some(1), None) if from.y == to.y => Some(Movement::RIGHT),
+            (Some(-1), None) if from.y == to.y => Some(Movement::LEFT),
+            (None, Some(1)) if from.x == to.x => Some(Movement::DOWN),
+            (None, Some(-1)) if from.x == to.x => Some(Movement::UP),
+            _ => Movement::between(from, to),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GameState {
+    #[cfg_attr(feature = "serde", serde(with = "save::board_rows"))]
     pub board: Vec<Vec<char>>,
     pub position: Position,
     pub win_position: Position,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub goals: Vec<Position>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub collected_goals: Vec<Position>,
+    #[cfg_attr(feature = "serde", serde(default, with = "save::position_map"))]
+    pub items: HashMap<Position, u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub score: u32,
+    /// Each portal cell and the partner stepping on it takes you to, both
+    /// ways round.
+    #[cfg_attr(feature = "serde", serde(default, with = "save::position_map"))]
+    pub portals: HashMap<Position, Position>,
+    /// One-way gates and the only direction they can be crossed in, both
+    /// onto and off the gate.
+    #[cfg_attr(feature = "serde", serde(default, with = "save::position_map"))]
+    pub one_way: HashMap<Position, Movement>,
+    /// Slippery cells, stepping onto one keeps the player sliding the same
+    /// way until something is in the way.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ice: HashSet<Position>,
+    /// Mud and what it costs to enter, counted as that many moves. Every
+    /// other cell costs 1.
+    #[cfg_attr(feature = "serde", serde(default, with = "save::position_map"))]
+    pub costs: HashMap<Position, u32>,
+    /// Diagonal moves are allowed, turning a corner in one press.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub diagonals: bool,
+    /// Visited cells can't be entered again and there's no undo. Getting
+    /// boxed in by the trail loses.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hardcore: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub enemies: Vec<Position>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub victory: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub failure: bool,
     pub time_remaining: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub moves: usize,
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+    pub started: Instant,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub finished: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub visited: HashSet<Position>,
+    #[cfg_attr(feature = "serde", serde(default = "save::trail_shown"))]
+    pub show_trail: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub visibility_radius: Option<usize>,
+    /// Seconds between the fog closing in by one cell, None while it holds
+    /// still. Once it reaches the player the game is lost.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fog_closing: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub facing: Option<Movement>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub history: Vec<Position>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub message: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub ticks: u64,
+    /// The tick play begins on after a countdown, 0 without one. Until then
+    /// the clock and enemies wait and the players can't move.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub starts_at: u64,
+    /// Measured when the maze is built, None for loaded games.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub stats: Option<MazeStats>,
+    /// Moving off an edge comes back on the opposite one.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap: bool,
+    /// The next cell on the shortest route and the tick it stops showing.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hint: Option<(Position, u64)>,
+    /// Distances from the start, None while the heat map is hidden.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub heat_map: Option<HeatMap>,
+    /// The whole route to the nearest goal, None while it's hidden.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub solution_path: Option<HashSet<Position>>,
+    /// A search being shown off, see `solve::explore`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub exploration: Option<solve::Exploration>,
+    /// The second player in a race, who starts alongside the first. None
+    /// outside races.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rival: Option<Player>,
+    /// Which player won a race, 0 for the first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub winner: Option<usize>,
+    /// Moves allowed by the par challenge, None when not playing for par.
+    /// Going over marks the attempt failed without ending the game.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub par: Option<usize>,
+    /// Moves rejected by a wall or the board edge, the renderer rings the
+    /// bell when it goes up.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bumps: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub paused: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub paused_at: Option<Instant>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub paused_total: Duration,
+    /// Steps zoomed out from the usual cell width, see
+    /// `RenderConfig::cell_width`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub zoom: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     x: usize,
     y: usize,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Position {
+    /// new
+    pub fn new(x: usize, y: usize) -> Position {
+        Position { x, y }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+/// Player
+/// Where a racing player is, drawn over the board rather than on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Player {
+    pub position: Position,
+    pub facing: Option<Movement>,
+    pub moves: usize,
+}
+
+/// Walls
+/// Which sides of a cell are closed off, by a wall or the edge of the
+/// board. Edges that wrap are open unless a wall is on the far side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Walls {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl Walls {
+    /// blocks
+    /// Whether the side `action` leaves by is closed, diagonals never
+    /// being a side.
+    pub fn blocks(&self, action: Movement) -> bool {
+        match action {
+            Movement::UP => self.up,
+            Movement::DOWN => self.down,
+            Movement::LEFT => self.left,
+            Movement::RIGHT => self.right,
+            Movement::UP_LEFT | Movement::UP_RIGHT | Movement::DOWN_LEFT | Movement::DOWN_RIGHT => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BoardCell {
     wall_right: bool,
     wall_bottom: bool,
 }
 
+/// GameSetup
+/// Everything needed to generate a game, the same setup always builds the
+/// same maze, goals, items and enemies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameSetup {
+    pub seed: u64,
+    pub algorithm: MazeAlgorithm,
+    pub width: usize,
+    pub height: usize,
+    pub braid: f64,
+    pub goals: usize,
+    pub items: usize,
+    pub enemies: usize,
+    /// Build a toroidal maze instead, `algorithm` and `braid` are ignored.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap: bool,
+    /// Overrides the usual start and goal, see `GameState::with_endpoints`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start: Option<Position>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub goal: Option<Position>,
+    /// Move the goal to the cell furthest from the start, ignoring `goal`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub longest: bool,
+    /// Pairs of portals linking distant cells.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub portals: usize,
+    /// One-way gates placed along the route to the goal.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub one_way: usize,
+    /// Ice cells, the goal is kept reachable.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ice: usize,
+    /// Mud cells that cost extra moves to enter.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mud: usize,
+    /// Allow diagonal moves, see `GameState::diagonals`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub diagonals: bool,
+    /// Never let the player recross their own trail.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hardcore: bool,
+}
+
+impl Default for GameSetup {
+    /// A medium sized perfect maze with a single goal and nothing else in
+    /// it, from seed 0.
+    fn default() -> GameSetup {
+        let size = Difficulty::Medium.size();
+        GameSetup {
+            seed: 0,
+            algorithm: MazeAlgorithm::default(),
+            width: size,
+            height: size,
+            braid: 0.0,
+            goals: 1,
+            items: 0,
+            enemies: 0,
+            wrap: false,
+            start: None,
+            goal: None,
+            longest: false,
+            portals: 0,
+            one_way: 0,
+            ice: 0,
+            mud: 0,
+            diagonals: false,
+            hardcore: false,
+        }
+    }
+}
+
+impl GameSetup {
+    /// build
+    /// Panics if the start or goal can't be used, see `try_build`.
+    pub fn build(&self) -> GameState {
+        self.try_build().unwrap_or_else(|e| panic!("invalid game setup: {e}"))
+    }
+
+    /// try_build
+    /// Builds the game, or explains why the start or goal can't be used.
+    pub fn try_build(&self) -> Result<GameState, String> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut state = match self.wrap {
+            true => GameState::wrapped(self.width, self.height, &mut rng),
+            false => GameState::with_options(self.algorithm, self.width, self.height, self.braid, &mut rng),
+        };
+        state.diagonals = self.diagonals;
+        state.hardcore = self.hardcore;
+        if self.start.is_some() || self.goal.is_some() {
+            let (start, goal) = (self.start.unwrap_or(state.position), self.goal.unwrap_or(state.win_position));
+            state = state.with_endpoints(start, goal)?;
+        }
+        if self.longest {
+            state = state.with_farthest_goal();
+        }
+        state.scatter_goals(self.goals.saturating_sub(1), &mut rng);
+        state.scatter_items(self.items, &mut rng);
+        state.spawn_enemies(self.enemies, &mut rng);
+        state.scatter_portals(self.portals, &mut rng);
+        state.place_one_way_gates(self.one_way, &mut rng);
+        state.scatter_ice(self.ice, &mut rng);
+        state.scatter_mud(self.mud, &mut rng);
+        Ok(state)
+    }
+
+    /// title
+    /// The seed, algorithm and size, enough to share the maze.
+    pub fn title(&self) -> String {
+        let algorithm = if self.wrap { "Wrapped".to_string() } else { self.algorithm.to_string() };
+        format!("Seed {}  {algorithm}  {}x{}", self.seed, self.width, self.height)
+    }
+
+    /// generation_frames
+    /// Up to `max` render boards, evenly spaced through the steps `build`
+    /// generates the maze in and ending on the finished maze, for
+    /// animating. Braiding, goals and the rest aren't shown. Empty for
+    /// wrapped mazes.
+    pub fn generation_frames(&self, max: usize) -> Vec<Vec<Vec<char>>> {
+        if self.wrap || max == 0 {
+            return vec![];
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let steps = generate_maze_steps(self.algorithm, self.width, self.height, &mut rng);
+        let every = steps.len().div_ceil(max);
+        let last = steps.len() - 1;
+        steps.into_iter().enumerate()
+            .filter(|&(i, _)| (last - i).is_multiple_of(every))
+            .map(|(_, step)| convert_generated_maze(step))
+            .collect()
+    }
+}
+
 impl GameState {
     /// new
     pub fn new(board_size: usize) -> GameState {
-        let board = convert_generated_maze(generate_maze(board_size));
-        let board_size = board.len();
+        GameState::generate(|| generate_maze(board_size))
+    }
+
+    /// with_size
+    /// Builds a game on a `width` x `height` render board.
+    pub fn with_size(width: usize, height: usize) -> GameState {
+        GameState::generate(|| generate_maze_sized(width, height))
+    }
+
+    /// new_seeded
+    /// Builds a reproducible game, the maze layout is fixed by `seed`.
+    pub fn new_seeded(board_size: usize, seed: u64) -> GameState {
+        let mut rng = StdRng::seed_from_u64(seed);
+        GameState::generate(|| generate_maze_with_rng(board_size, board_size, &mut rng))
+    }
+
+    /// new_with_difficulty
+    /// Builds a game sized and braided by the difficulty preset.
+    pub fn new_with_difficulty(d: Difficulty) -> GameState {
+        GameState::braided(d.size(), d.size(), d.braid_factor())
+    }
+
+    /// braided
+    /// Builds a `width` x `height` game with dead ends opened up by `factor`.
+    pub fn braided(width: usize, height: usize, factor: f64) -> GameState {
+        GameState::with_options(MazeAlgorithm::default(), width, height, factor, &mut rand::thread_rng())
+    }
+
+    /// with_options
+    /// Builds a `width` x `height` game generated by `algo`, with dead ends
+    /// opened up by `factor`. A seeded `rng` makes the layout reproducible.
+    pub fn with_options<R: Rng>(algo: MazeAlgorithm, width: usize, height: usize, factor: f64, rng: &mut R) -> GameState {
+        let mut board = convert_generated_maze(generate_maze_with(algo, width, height, rng));
+        braid(&mut board, factor, rng);
+        let (w, h) = (board[0].len(), board.len());
+        GameState::from_board(board, Position { x: 0, y: 0 }, Position { x: w-1, y: h-1 })
+    }
+
+    /// with_endpoints
+    /// Moves the player to `start` and the goal to `goal`, both render
+    /// board cells. Fails if either is off the board or on a wall, or
+    /// they're the same cell. Only meant for games that haven't started.
+    pub fn with_endpoints(mut self, start: Position, goal: Position) -> Result<GameState, String> {
+        let (width, height) = (self.width(), self.height());
+        for (name, pos) in [("start", start), ("goal", goal)] {
+            if pos.x >= width || pos.y >= height {
+                return Err(format!("{name} ({}, {}) is outside the {width}x{height} board", pos.x, pos.y));
+            }
+            if self.board[pos.y][pos.x] == WALL {
+                return Err(format!("{name} ({}, {}) is a wall", pos.x, pos.y));
+            }
+        }
+        if start == goal {
+            return Err(format!("start and goal are both ({}, {})", start.x, start.y));
+        }
+
+        let old_goal = self.win_position;
+        self.board[self.position.y][self.position.x] = ' ';
+        self.board[old_goal.y][old_goal.x] = ' ';
+        self.goals.retain(|&g| g != old_goal && g != start && g != goal);
+        self.goals.push(goal);
+        self.position = start;
+        self.win_position = goal;
+        self.board[start.y][start.x] = SYMBOL;
+        self.board[goal.y][goal.x] = GOAL;
+        self.refresh_stats();
+        Ok(self)
+    }
+
+    /// set_par
+    /// Challenges the player to win within `factor` times the fewest moves
+    /// possible, rounded up. Does nothing if the goal can't be reached.
+    pub fn set_par(&mut self, factor: f64) {
+        self.par = self.fewest_moves().map(|n| (n as f64 * factor).ceil() as usize);
+    }
+
+    /// fewest_moves
+    /// The fewest moves collecting every goal from here takes, heading for
+    /// the nearest one left each time. Counts presses the way the game does,
+    /// so a portal hop or a slide over ice is one move, and counts mud.
+    /// None if a goal can't be reached.
+    pub fn fewest_moves(&self) -> Option<usize> {
+        let mut goals = self.goals.clone();
+        let (mut from, mut moves) = (self.position, 0);
+        goals.retain(|&g| g != from);
+        while !goals.is_empty() {
+            if self.needs_plan() {
+                for action in self.plan(from, &goals)?.1 {
+                    let cells = self.slide(from, action);
+                    from = cells[cells.len() - 1];
+                    moves += self.cost(from);
+                    goals.retain(|g| !cells.contains(g));
+                }
+                continue;
+            }
+            let path = self.route(from, &goals)?;
+            // Stepping onto a portal lands on its partner in the same move.
+            moves += path
+                .windows(2)
+                .filter(|step| self.portals.get(&step[0]) != Some(&step[1]))
+                .map(|step| self.cost(self.portals.get(&step[1]).copied().unwrap_or(step[1])))
+                .sum::<usize>();
+            goals.retain(|g| !path.contains(g));
+            from = path[path.len() - 1];
+        }
+        Some(moves)
+    }
+
+    /// cost
+    /// The moves entering `pos` counts as.
+    pub fn cost(&self, pos: Position) -> usize {
+        self.costs.get(&pos).map_or(1, |&cost| cost as usize)
+    }
+
+    /// over_par
+    pub fn over_par(&self) -> bool {
+        self.par.is_some_and(|par| self.moves > par)
+    }
+
+    /// with_farthest_goal
+    /// Moves the goal to the cell the most steps from the player, so the
+    /// solution runs through as much of the maze as possible. Ties go to
+    /// the topmost, then leftmost cell.
+    pub fn with_farthest_goal(self) -> GameState {
+        let heat = HeatMap::new(&self.board, self.position, self.wrap);
+        let far = heat.distances.iter()
+            .max_by_key(|&(p, &d)| (d, std::cmp::Reverse((p.y, p.x))))
+            .map(|(&p, _)| p);
+        match far {
+            Some(goal) if goal != self.position => {
+                let start = self.position;
+                self.with_endpoints(start, goal).expect("reachable cells are open")
+            }
+            _ => self,
+        }
+    }
+
+    /// generate
+    /// Calls `gen` until it yields a maze whose goal is reachable from the start.
+    fn generate<F: FnMut() -> Vec<Vec<BoardCell>>>(mut gen: F) -> GameState {
+        loop {
+            let state = GameState::from_generated(gen());
+            if state.is_solvable() {
+                return state;
+            }
+        }
+    }
+
+    /// wrapped
+    /// Builds a game on a toroidal maze, where passages cross the edges.
+    /// The goal sits in the middle, the cell furthest from the start.
+    pub fn wrapped<R: Rng>(width: usize, height: usize, rng: &mut R) -> GameState {
+        let board = convert_wrapped_maze(generate_maze_torus(width, height, rng));
+        let middle = |len: usize| (len / 4) * 2;
+        let goal = Position { x: middle(board[0].len()), y: middle(board.len()) };
+        let mut state = GameState::from_board(board, Position { x: 0, y: 0 }, goal);
+        state.wrap = true;
+        state.refresh_stats();
+        state
+    }
+
+    /// from_generated
+    /// Builds a game from any generated maze grid.
+    pub fn from_generated(generated: Vec<Vec<BoardCell>>) -> GameState {
+        let board = convert_generated_maze(generated);
+        let (width, height) = (board[0].len(), board.len());
+        GameState::from_board(board, Position { x: 0, y: 0 }, Position { x: width-1, y: height-1 })
+    }
+
+    /// from_board
+    /// Builds a fresh game on a render board, placing the player and goal.
+    pub fn from_board(board: Vec<Vec<char>>, position: Position, win_position: Position) -> GameState {
+        let (width, height) = (board[0].len(), board.len());
         let mut state = GameState {
             board,
-            position: Position {
-                x: 0,
-                y: 0,
-            },
-            win_position: Position { x: board_size-1, y: board_size-1 },
+            position,
+            win_position,
+            goals: vec![win_position],
+            collected_goals: vec![],
+            items: HashMap::new(),
+            score: 0,
+            portals: HashMap::new(),
+            one_way: HashMap::new(),
+            ice: HashSet::new(),
+            costs: HashMap::new(),
+            diagonals: false,
+            hardcore: false,
+            enemies: vec![],
             victory: false,
             failure: false,
-            time_remaining: (board_size*10) as u32,
+            time_remaining: (width.max(height)*10) as u32,
+            moves: 0,
+            started: Instant::now(),
+            finished: None,
+            visited: HashSet::new(),
+            show_trail: true,
+            visibility_radius: None,
+            fog_closing: None,
+            facing: None,
+            history: vec![],
+            message: None,
+            ticks: 0,
+            starts_at: 0,
+            hint: None,
+            solution_path: None,
+            exploration: None,
+            heat_map: None,
+            stats: None,
+            wrap: false,
+            bumps: 0,
+            rival: None,
+            winner: None,
+            par: None,
+            paused: false,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            zoom: 0,
         };
         state.board[state.position.y][state.position.x] = SYMBOL;
         state.board[state.win_position.y][state.win_position.x] = GOAL;
+        state.refresh_stats();
 
         state
     }
 
+    /// board
+    /// Returns the render board, one char per cell.
+    pub fn board(&self) -> &[Vec<char>] {
+        &self.board
+    }
+
+    /// width
+    pub fn width(&self) -> usize {
+        self.board[0].len()
+    }
+
+    /// height
+    pub fn height(&self) -> usize {
+        self.board.len()
+    }
+
+    /// cells
+    /// Every open cell of the board, row by row, and its walls.
+    pub fn cells(&self) -> impl Iterator<Item = (Position, Walls)> + '_ {
+        let positions = (0..self.height()).flat_map(move |y| (0..self.width()).map(move |x| Position::new(x, y)));
+        positions.filter(|pos| self.board[pos.y][pos.x] != WALL).map(|pos| (pos, self.walls(pos)))
+    }
+
+    /// walls
+    /// The sides of `pos` closed off by a wall or the edge of the board.
+    /// Gates and the hardcore trail aren't walls, they only block the way
+    /// for a while or in one direction. Off the board every side is closed.
+    pub fn walls(&self, pos: Position) -> Walls {
+        if pos.x >= self.width() || pos.y >= self.height() {
+            return Walls { up: true, down: true, left: true, right: true };
+        }
+        let closed = |action| {
+            let next = self.step(pos, action);
+            next == pos || self.board[next.y][next.x] == WALL
+        };
+        Walls {
+            up: closed(Movement::UP),
+            down: closed(Movement::DOWN),
+            left: closed(Movement::LEFT),
+            right: closed(Movement::RIGHT),
+        }
+    }
+
+    /// are_connected
+    /// Whether a single step leads between open cells `a` and `b`, side by
+    /// side or across an edge that wraps. False for cells further apart,
+    /// diagonal or off the board.
+    pub fn are_connected(&self, a: Position, b: Position) -> bool {
+        let open = |pos: Position| pos.x < self.width() && pos.y < self.height() && self.board[pos.y][pos.x] != WALL;
+        if !open(a) || !open(b) {
+            return false;
+        }
+        let action = match self.wrap {
+            true => Movement::between_wrapping(a, b, (self.width(), self.height())),
+            false => Movement::between(a, b),
+        };
+        action.is_some_and(|action| !self.walls(a).blocks(action))
+    }
+
     /// move_position
     pub fn move_position(&mut self, action: Movement) {
-        let mut new_pos = Position {
-            y: self.position.y,
-            x: self.position.x,
+        if self.frozen() {
+            return;
+        }
+        self.facing = Some(action);
+        let cells = match self.victory || self.failure {
+            true => vec![],
+            false => self.slide(self.position, action),
         };
+        if cells.is_empty() {
+            if !self.victory && !self.failure {
+                self.bumps += 1;
+            }
+            return;
+        }
+
+        self.board[self.position.y][self.position.x] = ' ';
+        self.visited.insert(self.position);
+        self.history.push(self.position);
+        self.hint = None;
+        self.moves += self.cost(cells[cells.len() - 1]);
+        let last = cells.len() - 1;
+        for (i, pos) in cells.into_iter().enumerate() {
+            self.position = pos;
+            if let Some(points) = self.items.remove(&pos) {
+                self.score += points;
+            }
+            if let Some(g) = self.goals.iter().position(|&g| g == pos) {
+                self.collected_goals.push(self.goals.swap_remove(g));
+            }
+            // A slide stops as soon as the last goal is reached.
+            if i == last || self.is_win_position() {
+                break;
+            }
+            self.visited.insert(pos);
+        }
+        self.board[self.position.y][self.position.x] = SYMBOL;
+        self.refresh_solution_path();
 
+        if self.is_win_position() {
+            self.victory = true;
+            self.winner = self.rival.map(|_| 0);
+            self.stop_timer();
+        } else {
+            self.check_enemy_collision();
+            self.check_boxed_in();
+        }
+    }
+
+    /// check_boxed_in
+    /// Loses a hardcore game once the trail leaves no move to make.
+    fn check_boxed_in(&mut self) {
+        let actions = if self.diagonals { &Movement::ALL[..] } else { &Movement::ALL[..4] };
+        if self.hardcore && !self.failure && actions.iter().all(|&a| self.slide(self.position, a).is_empty()) {
+            self.failure = true;
+            self.message = Some("Boxed in by your own trail".to_string());
+            self.stop_timer();
+        }
+    }
+
+    /// add_rival
+    /// Starts a race against a second player on the same cell.
+    pub fn add_rival(&mut self) {
+        self.rival = Some(Player { position: self.position, facing: None, moves: 0 });
+    }
+
+    /// move_player
+    /// Moves player 0, the usual player, or player 1, the rival in a race.
+    /// The first to reach a goal wins.
+    pub fn move_player(&mut self, player: usize, action: Movement) {
+        match player {
+            0 => self.move_position(action),
+            _ => self.move_rival(action),
+        }
+    }
+
+    /// place_rival
+    /// Moves the rival straight to `pos`, for one whose moves are made
+    /// elsewhere, such as over the network.
+    pub fn place_rival(&mut self, pos: Position) {
+        let (width, height) = (self.width(), self.height());
+        if let Some(rival) = self.rival.as_mut().filter(|rival| rival.position != pos) {
+            rival.facing = Movement::between_wrapping(rival.position, pos, (width, height)).or(rival.facing);
+            rival.position = pos;
+            rival.moves += 1;
+        }
+    }
+
+    /// rival_won
+    /// Ends a race the rival won elsewhere, unless it's already over.
+    pub fn rival_won(&mut self) {
+        if self.rival.is_some() && !self.victory && !self.failure {
+            self.victory = true;
+            self.winner = Some(1);
+            self.stop_timer();
+        }
+    }
+
+    /// move_rival
+    /// Like `move_position` for the rival, who doesn't collect anything or
+    /// leave a trail and can't undo.
+    fn move_rival(&mut self, action: Movement) {
+        let Some(mut rival) = self.rival else {
+            return;
+        };
+        if self.frozen() {
+            return;
+        }
+        rival.facing = Some(action);
+        let cells = match self.victory || self.failure {
+            true => vec![],
+            false => self.slide(rival.position, action),
+        };
+        match cells.iter().position(|c| self.goals.contains(c)) {
+            _ if cells.is_empty() => if !self.victory && !self.failure {
+                self.bumps += 1;
+            },
+            Some(goal) => {
+                rival.position = cells[goal];
+                rival.moves += self.cost(rival.position);
+                self.victory = true;
+                self.winner = Some(1);
+                self.stop_timer();
+            }
+            None => {
+                rival.position = cells[cells.len() - 1];
+                rival.moves += self.cost(rival.position);
+            }
+        }
+        self.rival = Some(rival);
+    }
+
+    /// step
+    /// The cell next to `pos` in direction `action`, crossing the edges
+    /// when they wrap. `pos` itself at the edge of the board.
+    fn step(&self, pos: Position, action: Movement) -> Position {
+        if let Some((vertical, horizontal)) = action.parts() {
+            return self.step(self.step(pos, vertical), horizontal);
+        }
+        let (width, height) = (self.width(), self.height());
+        let mut next = pos;
         match action {
-            Movement::UP => if let Some(y) = self.position.y.checked_sub(1) {
-                new_pos.y = y;
+            _ if self.wrap => match action {
+                Movement::UP => next.y = (pos.y + height - 1) % height,
+                Movement::DOWN => next.y = (pos.y + 1) % height,
+                Movement::LEFT => next.x = (pos.x + width - 1) % width,
+                Movement::RIGHT => next.x = (pos.x + 1) % width,
+                Movement::UP_LEFT | Movement::UP_RIGHT | Movement::DOWN_LEFT | Movement::DOWN_RIGHT => (),
+            },
+            Movement::UP => if let Some(y) = pos.y.checked_sub(1) {
+                next.y = y;
             }
-            Movement::DOWN => if self.position.y < self.board.len()-1 {
-                new_pos.y = self.position.y+1;
+            Movement::DOWN => if pos.y < height-1 {
+                next.y = pos.y+1;
             }
-            Movement::LEFT => if let Some(x) = self.position.x.checked_sub(1) {
-                new_pos.x = x;
+            Movement::LEFT => if let Some(x) = pos.x.checked_sub(1) {
+                next.x = x;
             }
-            Movement::RIGHT => if self.position.x < self.board.len()-1 {
-                new_pos.x = self.position.x+1;
+            Movement::RIGHT => if pos.x < width-1 {
+                next.x = pos.x+1;
             }
+            // Diagonals were split up above.
+            Movement::UP_LEFT | Movement::UP_RIGHT | Movement::DOWN_LEFT | Movement::DOWN_RIGHT => (),
         }
+        next
+    }
 
-        if self.is_valid_move(&new_pos) {
-            self.board[self.position.y][self.position.x] = ' ';
-            self.board[new_pos.y][new_pos.x] = SYMBOL;
-            self.position = new_pos;
+    /// cross
+    /// Where one step of `action` takes the player from `pos`, None if it's
+    /// blocked. Pillars of wall sit between every four cells, so no
+    /// diagonal has both ways round its corner open. Instead a diagonal
+    /// goes round through whichever side is open, but never squeezes
+    /// between two walls or through a portal.
+    fn cross(&self, pos: Position, action: Movement) -> Option<Position> {
+        match action.parts() {
+            None => {
+                let next = self.step(pos, action);
+                self.can_cross(pos, next, action).then_some(next)
+            }
+            Some(_) if !self.diagonals => None,
+            Some((vertical, horizontal)) => {
+                [(vertical, horizontal), (horizontal, vertical)].into_iter().find_map(|(a, b)| {
+                    let corner = self.step(pos, a);
+                    let next = self.step(corner, b);
+                    let open = self.can_cross(pos, corner, a) && !self.portals.contains_key(&corner)
+                        && self.can_cross(corner, next, b);
+                    open.then_some(next)
+                })
+            }
+        }
+    }
 
-            if self.is_win_position() {
-                self.victory = true;
+    /// needs_plan
+    /// Whether a press can do more than step to a neighbor, so routes have
+    /// to be planned press by press.
+    fn needs_plan(&self) -> bool {
+        self.diagonals || self.hardcore || !self.ice.is_empty()
+    }
+
+    /// slide
+    /// Every cell pressing `action` at `from` moves the player through, in
+    /// order and ending where they stop, empty if the move is blocked.
+    /// Portals fire on landing, and the player keeps going while they land
+    /// on ice, even straight out of a portal. A slide stops before it would
+    /// go round in circles.
+    fn slide(&self, from: Position, action: Movement) -> Vec<Position> {
+        let mut cells = vec![];
+        let mut pos = from;
+        while cells.len() <= self.width() * self.height() {
+            let Some(next) = self.cross(pos, action) else {
+                break;
+            };
+            cells.push(next);
+            // Portals fire once, arriving on the partner doesn't jump back.
+            pos = match self.portals.get(&next) {
+                Some(&partner) => {
+                    cells.push(partner);
+                    partner
+                }
+                None => next,
+            };
+            if !self.ice.contains(&pos) {
+                break;
+            }
+        }
+        cells
+    }
+
+    /// plan
+    /// Fewest key presses from `from` to the nearest of `goals`, sliding
+    /// over ice. Returns every cell passed through, as a path, and the
+    /// presses that walk it.
+    fn plan(&self, from: Position, goals: &[Position]) -> Option<(Vec<Position>, Vec<Movement>)> {
+        let actions = if self.diagonals { &Movement::ALL[..] } else { &Movement::ALL[..4] };
+        let mut came_from: HashMap<Position, (Position, Movement, Vec<Position>)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        let mut reached = goals.contains(&from).then_some((from, 0));
+        'search: while let Some(pos) = queue.pop_front() {
+            if reached.is_some() {
+                break;
+            }
+            for &action in actions {
+                let cells = self.slide(pos, action);
+                if let Some(hit) = cells.iter().position(|c| goals.contains(c)) {
+                    came_from.insert(cells[hit], (pos, action, cells[..=hit].to_vec()));
+                    reached = Some((cells[hit], hit + 1));
+                    continue 'search;
+                }
+                if let Some(&end) = cells.last() {
+                    if end != from && !came_from.contains_key(&end) {
+                        came_from.insert(end, (pos, action, cells));
+                        queue.push_back(end);
+                    }
+                }
+            }
+        }
+        let (mut cur, _) = reached?;
+        let (mut cells, mut actions) = (vec![], vec![]);
+        while cur != from {
+            let (prev, action, passed) = &came_from[&cur];
+            cells.extend(passed.iter().rev());
+            actions.push(*action);
+            cur = *prev;
+        }
+        cells.push(from);
+        cells.reverse();
+        actions.reverse();
+        Some((cells, actions))
+    }
+
+    /// spawn_enemies
+    /// Places `count` enemies on random open cells at least half the board
+    /// away from the player, so the game doesn't open on a collision.
+    pub fn spawn_enemies<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let min_dist = self.width().max(self.height()) / 2;
+        let mut open: Vec<Position> = (0..self.height()).step_by(2)
+            .flat_map(|y| (0..self.width()).step_by(2).map(move |x| Position { x, y }))
+            .filter(|p| self.board[p.y][p.x] == ' ')
+            .filter(|p| p.x.abs_diff(self.position.x).max(p.y.abs_diff(self.position.y)) >= min_dist)
+            .collect();
+        open.shuffle(rng);
+        self.enemies.extend(open.into_iter().take(count));
+    }
+
+    /// update_enemies
+    /// Moves every enemy one cell. Each step an enemy either closes in on
+    /// the player along the shortest path or wanders to a random open
+    /// neighbor, with even odds. Touching the player ends the game.
+    pub fn update_enemies<R: Rng>(&mut self, rng: &mut R) {
+        if self.victory || self.failure {
+            return;
+        }
+        for i in 0..self.enemies.len() {
+            let enemy = self.enemies[i];
+            let next = if rng.gen_bool(0.5) {
+                self.route(enemy, &[self.position]).and_then(|path| path.get(1).copied())
+            } else {
+                self.open_neighbors(enemy).choose(rng).copied()
+            };
+            if let Some(next) = next {
+                self.enemies[i] = next;
             }
         }
+        self.check_enemy_collision();
+    }
+
+    fn check_enemy_collision(&mut self) {
+        if self.enemies.contains(&self.position) {
+            self.failure = true;
+            self.stop_timer();
+        }
+    }
+
+    /// undo
+    /// Steps back to the previous position.
+    /// No-op at the start position and once the game is over.
+    pub fn undo(&mut self) {
+        if self.victory || self.failure || self.frozen() || self.hardcore {
+            return;
+        }
+        if let Some(prev) = self.history.pop() {
+            self.board[self.position.y][self.position.x] = ' ';
+            self.board[prev.y][prev.x] = SYMBOL;
+            self.moves = self.moves.saturating_sub(self.cost(self.position));
+            self.position = prev;
+            self.refresh_solution_path();
+        }
     }
 
     fn clock_event(&mut self, ev: Clock) {
@@ -122,45 +1117,596 @@ impl GameState {
 
         if self.time_remaining == 0 && !self.victory {
             self.failure = true;
+            self.stop_timer();
         }
     }
 
-    /// is_valid_move
-    /// Accepts a board reference and the destination position.
-    /// Returns true if move is valid, otherwise false.
-    fn is_valid_move(&self, new_position: &Position) -> bool {
-        !self.victory && !self.failure && self.board[new_position.y][new_position.x] != WALL
+    /// tick
+    /// Advances time based updates by one TICK: the countdown every second,
+    /// the enemies every few ticks and the fog when it's closing in.
+    /// Returns true if anything changed and a new frame is needed.
+    pub fn tick<R: Rng>(&mut self, rng: &mut R) -> bool {
+        if self.paused {
+            return false;
+        }
+        self.ticks += 1;
+        if self.ticks < self.starts_at {
+            return (self.starts_at - self.ticks).is_multiple_of(TICKS_PER_SECOND);
+        }
+        let mut changed = false;
+        if self.ticks == self.starts_at {
+            self.started = Instant::now();
+            changed = true;
+        }
+        if self.starts_at > 0 && self.ticks == self.starts_at + GO_TICKS {
+            changed = true;
+        }
+        let played = self.ticks - self.starts_at;
+        if self.hint.is_some_and(|(_, until)| self.ticks >= until) {
+            self.hint = None;
+            changed = true;
+        }
+        if played > 0 && played.is_multiple_of(TICKS_PER_SECOND) {
+            self.clock_event(Clock::SUB(1));
+            changed = true;
+        }
+        if !self.enemies.is_empty() && played > 0 && played.is_multiple_of(TICKS_PER_ENEMY_STEP) {
+            self.update_enemies(rng);
+            changed = true;
+        }
+        if let Some(secs) = self.fog_closing.filter(|&secs| secs > 0) {
+            if played > 0 && played.is_multiple_of(secs.saturating_mul(TICKS_PER_SECOND)) {
+                self.close_fog();
+                changed = true;
+            }
+        }
+        changed
     }
 
+    /// close_fog
+    /// Shrinks the fog of war by a cell, losing the game once it leaves
+    /// only the player's own cell in sight.
+    fn close_fog(&mut self) {
+        let Some(radius) = self.visibility_radius.as_mut() else {
+            return;
+        };
+        *radius = radius.saturating_sub(1);
+        if *radius == 0 && !self.victory {
+            self.failure = true;
+            self.message = Some("The fog closed in".to_string());
+            self.stop_timer();
+        }
+    }
+
+    /// fog_closes_in
+    /// Seconds until the fog next closes in, None if it isn't closing or
+    /// the game is over.
+    pub fn fog_closes_in(&self) -> Option<u64> {
+        let secs = self.fog_closing.filter(|&secs| secs > 0 && self.visibility_radius.is_some())?;
+        if self.victory || self.failure {
+            return None;
+        }
+        let played = self.ticks.saturating_sub(self.starts_at);
+        let period = secs.saturating_mul(TICKS_PER_SECOND);
+        Some((period - played % period).div_ceil(TICKS_PER_SECOND))
+    }
+
+    /// start_countdown
+    /// Holds the game for `secs` seconds of ticks before play begins, the
+    /// timer starting from then.
+    pub fn start_countdown(&mut self, secs: u64) {
+        self.starts_at = match secs {
+            0 => 0,
+            secs => self.ticks + secs * TICKS_PER_SECOND,
+        };
+    }
+
+    /// countdown
+    /// Whole seconds left before play begins, or Some(0) while "GO!" is
+    /// shown just after. None once the countdown is over or without one.
+    pub fn countdown(&self) -> Option<u64> {
+        match self.starts_at {
+            0 => None,
+            at if self.ticks >= at + GO_TICKS => None,
+            at => Some(at.saturating_sub(self.ticks).div_ceil(TICKS_PER_SECOND)),
+        }
+    }
+
+    /// frozen
+    /// True while paused or counting down, when nobody may move.
+    fn frozen(&self) -> bool {
+        self.paused || self.ticks < self.starts_at
+    }
+
+    /// toggle_pause
+    /// Pauses or resumes the game. While paused the clock, enemies and
+    /// movement are frozen, and the time is left out of `elapsed`.
+    pub fn toggle_pause(&mut self) {
+        if self.victory || self.failure || self.ticks < self.starts_at {
+            return;
+        }
+        match self.paused_at.take() {
+            Some(at) => self.paused_total += at.elapsed(),
+            None => self.paused_at = Some(Instant::now()),
+        }
+        self.paused = self.paused_at.is_some();
+    }
+
+    /// elapsed
+    /// Time spent on this maze, not counting pauses, frozen once the game
+    /// is over.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.running_time())
+    }
+
+    fn running_time(&self) -> Duration {
+        let paused = self.paused_total + self.paused_at.map(|at| at.elapsed()).unwrap_or_default();
+        self.started.elapsed().saturating_sub(paused)
+    }
+
+    fn stop_timer(&mut self) {
+        if self.finished.is_none() {
+            self.finished = Some(self.running_time());
+        }
+    }
+
+    /// can_cross
+    /// Whether `action` can take the player from `from` onto the cell `to`
+    /// next to it. Staying put, at the edge of the board, isn't a move, and
+    /// one-way gates only let `action` through in their own direction. In
+    /// hardcore games the trail is as good as a wall.
+    fn can_cross(&self, from: Position, to: Position, action: Movement) -> bool {
+        let gate_allows = |pos: &Position| self.one_way.get(pos).is_none_or(|&dir| dir == action);
+        to != from && self.board[to.y][to.x] != WALL && gate_allows(&from) && gate_allows(&to)
+            && !(self.hardcore && self.visited.contains(&to))
+    }
+
+    /// is_win_position
+    /// True once every goal has been collected.
     fn is_win_position(&self) -> bool {
-        self.position == self.win_position
+        self.goals.is_empty()
+    }
+
+    /// scatter_goals
+    /// Adds `count` extra goals on random open cells, all of which must be
+    /// collected to win. Goals are only placed on cells the generator
+    /// carved, never on walls, the player or an existing goal.
+    pub fn scatter_goals<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let mut open: Vec<Position> = (0..self.height()).step_by(2)
+            .flat_map(|y| (0..self.width()).step_by(2).map(move |x| Position { x, y }))
+            .filter(|&p| self.board[p.y][p.x] == ' ')
+            .collect();
+        open.shuffle(rng);
+        for goal in open.into_iter().take(count) {
+            self.board[goal.y][goal.x] = GOAL;
+            self.goals.push(goal);
+        }
+        self.refresh_stats();
+    }
+
+    /// scatter_items
+    /// Places `count` collectible items on random open cells, each worth
+    /// one of ITEM_POINTS.
+    pub fn scatter_items<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let mut open: Vec<Position> = (0..self.height()).step_by(2)
+            .flat_map(|y| (0..self.width()).step_by(2).map(move |x| Position { x, y }))
+            .filter(|p| self.board[p.y][p.x] == ' ' && !self.items.contains_key(p))
+            .collect();
+        open.shuffle(rng);
+        for pos in open.into_iter().take(count) {
+            self.items.insert(pos, ITEM_POINTS[rng.gen_range(0..ITEM_POINTS.len())]);
+        }
+    }
+
+    /// scatter_portals
+    /// Links `count` pairs of random open cells with portals. Portals never
+    /// share a cell with the player, a goal, an item or an enemy.
+    pub fn scatter_portals<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let mut open: Vec<Position> = (0..self.height()).step_by(2)
+            .flat_map(|y| (0..self.width()).step_by(2).map(move |x| Position { x, y }))
+            .filter(|p| {
+                self.board[p.y][p.x] == ' ' && !self.items.contains_key(p)
+                    && !self.enemies.contains(p) && !self.portals.contains_key(p)
+            })
+            .collect();
+        open.shuffle(rng);
+        for pair in open.chunks_exact(2).take(count) {
+            self.portals.insert(pair[0], pair[1]);
+            self.portals.insert(pair[1], pair[0]);
+        }
+    }
+
+    /// place_one_way_gates
+    /// Turns up to `count` passages on the route to the nearest goal into
+    /// one-way gates facing along it, so the maze stays solvable. Gates only
+    /// go between cells, never on a cell itself or a portal.
+    pub fn place_one_way_gates<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let Some(path) = self.solution() else {
+            return;
+        };
+        let (width, height) = (self.width(), self.height());
+        let mut gates: Vec<(Position, Movement)> = path.windows(2)
+            .filter(|w| (w[1].x % 2 == 1 || w[1].y % 2 == 1) && !self.portals.contains_key(&w[1]))
+            .filter_map(|w| Movement::between_wrapping(w[0], w[1], (width, height)).map(|dir| (w[1], dir)))
+            .filter(|(_, dir)| dir.parts().is_none())
+            .collect();
+        gates.shuffle(rng);
+        self.one_way.extend(gates.into_iter().take(count));
+    }
+
+    /// scatter_ice
+    /// Freezes up to `count` random open cells, skipping any that would
+    /// leave a goal out of reach from the start.
+    pub fn scatter_ice<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let mut open: Vec<Position> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| Position { x, y }))
+            .filter(|p| {
+                self.board[p.y][p.x] == ' ' && !self.portals.contains_key(p) && !self.one_way.contains_key(p)
+            })
+            .collect();
+        open.shuffle(rng);
+        for pos in open {
+            if self.ice.len() == count {
+                break;
+            }
+            self.ice.insert(pos);
+            if !self.goals.iter().all(|&goal| self.plan(self.position, &[goal]).is_some()) {
+                self.ice.remove(&pos);
+            }
+        }
+    }
+
+    /// scatter_mud
+    /// Turns up to `count` random open cells into mud costing 2 to
+    /// MAX_MUD_COST moves to enter. Mud only slows the way down, every goal
+    /// stays reachable.
+    pub fn scatter_mud<R: Rng>(&mut self, count: usize, rng: &mut R) {
+        let mut open: Vec<Position> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| Position { x, y }))
+            .filter(|p| {
+                self.board[p.y][p.x] == ' ' && !self.items.contains_key(p) && !self.portals.contains_key(p)
+                    && !self.one_way.contains_key(p) && !self.ice.contains(p)
+            })
+            .collect();
+        open.shuffle(rng);
+        for pos in open.into_iter().take(count) {
+            self.costs.insert(pos, rng.gen_range(2..=MAX_MUD_COST));
+        }
+    }
+
+    /// is_visible
+    /// Whether `pos` is within the fog of war radius of the player, using
+    /// Chebyshev distance. Everything is visible when there is no fog.
+    pub fn is_visible(&self, pos: Position) -> bool {
+        match self.visibility_radius {
+            Some(r) => self.position.x.abs_diff(pos.x).max(self.position.y.abs_diff(pos.y)) <= r,
+            None => true,
+        }
+    }
+
+    /// is_solvable
+    /// Whether every remaining goal can be reached from the player.
+    pub fn is_solvable(&self) -> bool {
+        self.goals.iter().all(|&g| self.route(self.position, &[g]).is_some())
+    }
+
+    /// solution
+    /// Shortest path from the player to the nearest remaining goal.
+    pub fn solution(&self) -> Option<Vec<Position>> {
+        self.route(self.position, &self.goals)
+    }
+
+    /// route
+    /// Shortest path from `from` to the nearest of `goals`, crossing the
+    /// edges when they wrap. Mud makes it the cheapest path instead, except
+    /// where presses are planned or portals and gates followed, which
+    /// ignore costs.
+    fn route(&self, from: Position, goals: &[Position]) -> Option<Vec<Position>> {
+        if self.needs_plan() {
+            return self.plan(from, goals).map(|(cells, _)| cells);
+        }
+        match (self.wrap, self.portals.is_empty() && self.one_way.is_empty()) {
+            (wrap, true) if !self.costs.is_empty() => solve_weighted(&self.board, from, goals, &self.costs, wrap),
+            (true, true) => solve_nearest_wrapping(&self.board, from, goals),
+            (false, true) if goals.len() == 1 => solve_astar(&self.board, from, goals[0], manhattan),
+            (false, true) => solve_nearest(&self.board, from, goals),
+            (wrap, false) => solve_nearest_through(&self.board, from, goals, &self.portals, &self.one_way, wrap),
+        }
+    }
+
+    /// open_neighbors
+    fn open_neighbors(&self, pos: Position) -> Vec<Position> {
+        match self.wrap {
+            true => wrapping_neighbors(&self.board, pos),
+            false => neighbors(&self.board, pos),
+        }
     }
 
-    pub fn listen(&mut self, rx: Receiver<StateEvent>, sx: Sender<GameState>) {
-        if let Err(e) = sx.send(self.clone()) {
-            panic!("Could not send board state to render {e}");
+    /// refresh_stats
+    fn refresh_stats(&mut self) {
+        self.stats = Some(match self.wrap {
+            true => analyze_wrapping(&self.board),
+            false => analyze(&self.board),
+        });
+    }
+
+    /// show_hint
+    /// Highlights the next cell on the shortest route to the nearest goal
+    /// for HINT_TICKS. Does nothing once the game is over or paused.
+    pub fn show_hint(&mut self) {
+        if self.frozen() || self.victory || self.failure {
+            return;
+        }
+        if let Some(&next) = self.solution().as_ref().and_then(|path| path.get(1)) {
+            self.hint = Some((next, self.ticks + HINT_TICKS));
+        }
+    }
+
+    /// toggle_solution_path
+    /// Shows or hides the whole route to the nearest goal, while the player
+    /// keeps control.
+    pub fn toggle_solution_path(&mut self) {
+        self.solution_path = match self.solution_path {
+            Some(_) => None,
+            None => Some(self.solution().unwrap_or_default().into_iter().collect()),
+        };
+    }
+
+    /// toggle_heat_map
+    /// Shows or hides every cell's distance from where the player started.
+    pub fn toggle_heat_map(&mut self) {
+        self.heat_map = match self.heat_map {
+            Some(_) => None,
+            None => {
+                let start = self.history.first().copied().unwrap_or(self.position);
+                Some(HeatMap::new(&self.board, start, self.wrap))
+            }
+        };
+    }
+
+    /// refresh_solution_path
+    /// Reroutes a showing solution path from the player's new position.
+    fn refresh_solution_path(&mut self) {
+        if self.solution_path.is_some() {
+            self.solution_path = Some(self.solution().unwrap_or_default().into_iter().collect());
+        }
+    }
+
+    /// auto_solve
+    /// Walks the player along the solution by feeding movements back into
+    /// `tx`, one every SOLVE_STEP_DELAY, so each step renders as a frame.
+    fn auto_solve(&self, tx: Sender<StateEvent>) {
+        if self.frozen() {
+            return;
+        }
+        let moves = match !self.needs_plan() {
+            true => self.solution().map(|path| match self.wrap {
+                true => path_to_wrapping_movements(&path, (self.width(), self.height())),
+                false => path_to_movements(&path),
+            }),
+            false => self.plan(self.position, &self.goals).map(|(_, moves)| moves),
+        };
+        if let Some(moves) = moves {
+            thread::spawn(move || {
+                for mv in moves {
+                    thread::sleep(SOLVE_STEP_DELAY);
+                    if tx.send(StateEvent::Movement(mv)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    /// restart
+    /// Puts the game back to `initial`, how it stood when play began, with
+    /// the clock starting again. The maze is the same but the trail, moves
+    /// and anything collected are gone. Races can't be restarted, as the
+    /// other player would play on.
+    pub fn restart(&mut self, initial: &GameState) {
+        if self.rival.is_some() {
+            self.message = Some("Races can't be restarted".to_string());
+            return;
+        }
+        *self = GameState {
+            started: Instant::now(),
+            show_trail: self.show_trail,
+            zoom: self.zoom,
+            message: Some("Restarted".to_string()),
+            ..initial.clone()
+        };
+    }
+
+    /// listen
+    /// Applies events from `rx` and sends each resulting state to `sx`.
+    /// Enemies move using `rng`. The event hook sees every event before it
+    /// is applied, and the finish hook sees the state each time the game is
+    /// won. Once it's over only restarting, zooming, saving and copying do
+    /// anything.
+    /// Returns once nothing is left to draw the game, dropping `rx` so the
+    /// next event sent fails and the sender can shut down.
+    pub fn listen(
+        &mut self,
+        rx: Receiver<StateEvent>,
+        tx: Sender<StateEvent>,
+        sx: Sender<GameState>,
+        rng: &mut StdRng,
+        hooks: Hooks,
+    ) {
+        let Hooks { event: mut hook, mut finish } = hooks;
+        let initial = self.clone();
+        // Auto-solved games, or ones played with the solution showing,
+        // don't count as won by the player.
+        let mut scored = true;
+        if sx.send(self.clone()).is_err() {
+            return;
         }
         while let Ok(ev) = rx.recv() {
+            if matches!(ev, StateEvent::Quit) {
+                return;
+            }
+            let over = self.victory || self.failure;
+            #[cfg(feature = "serde")]
+            let saving = matches!(ev, StateEvent::Save(_));
+            #[cfg(not(feature = "serde"))]
+            let saving = false;
+            if over && !saving && !matches!(ev, StateEvent::Restart | StateEvent::Copy | StateEvent::Zoom) {
+                continue;
+            }
+            if let Some(hook) = hook.as_mut() {
+                hook(&ev);
+            }
             match ev {
-                StateEvent::Movement(ev) => self.move_position(ev),
+                StateEvent::Movement(ev) => {
+                    self.message = None;
+                    self.move_position(ev);
+                }
+                StateEvent::PlayerMovement(player, ev) => {
+                    self.message = None;
+                    self.move_player(player, ev);
+                }
+                StateEvent::RivalMoved(pos) => self.place_rival(pos),
+                StateEvent::RivalWon => self.rival_won(),
+                StateEvent::RivalLeft => self.message = Some("Player 2 left".to_string()),
                 StateEvent::Clock(ev) => self.clock_event(ev),
-                StateEvent::NoOP => (),
+                StateEvent::Solve => {
+                    scored = false;
+                    self.auto_solve(tx.clone());
+                }
+                StateEvent::Hint => self.show_hint(),
+                StateEvent::ToggleTrail => self.show_trail = !self.show_trail,
+                StateEvent::ToggleHeatMap => self.toggle_heat_map(),
+                StateEvent::Zoom => self.zoom = (self.zoom + 1) % CELL_WIDTHS.len(),
+                StateEvent::ToggleSolution => {
+                    scored = false;
+                    self.toggle_solution_path();
+                }
+                StateEvent::Undo => self.undo(),
+                StateEvent::Pause => self.toggle_pause(),
+                StateEvent::Tick => if !self.tick(rng) {
+                    continue;
+                },
+                #[cfg(feature = "serde")]
+                StateEvent::Save(path) => {
+                    self.message = Some(match self.save(&path) {
+                        Ok(()) => format!("Saved to {}", path.display()),
+                        Err(e) => format!("Save failed: {e}"),
+                    });
+                }
+                StateEvent::Copy => {
+                    self.message = Some(match clipboard::copy(&export_ascii(&self.board)) {
+                        Ok(()) => "Copied the maze to the clipboard".to_string(),
+                        Err(e) => format!("Copy failed: {e}"),
+                    });
+                }
+                StateEvent::Restart => {
+                    scored = true;
+                    self.restart(&initial);
+                }
+                StateEvent::Quit | StateEvent::NoOP => (),
+            }
+            if sx.send(self.clone()).is_err() {
+                return;
             }
-            let _ = sx.send(self.clone());
-            if self.victory || self.failure {
-                return
+            if self.victory && !over && scored {
+                if let Some(message) = finish.as_mut().and_then(|f| f(self)) {
+                    self.message = Some(message);
+                    if sx.send(self.clone()).is_err() {
+                        return;
+                    }
+                }
             }
         }
     }
 }
 
+/// EventHook
+/// Called with every event the game handles, e.g. to record it.
+pub type EventHook = Box<dyn FnMut(&StateEvent) + Send>;
+
+/// FinishHook
+/// Called with each winning state, e.g. to update the leaderboard. Any
+/// message it returns is shown in the status line.
+pub type FinishHook = Box<dyn FnMut(&GameState) -> Option<String> + Send>;
+
+/// Hooks
+/// Callbacks run on the game thread, see `GameState::listen`.
+#[derive(Default)]
+pub struct Hooks {
+    pub event: Option<EventHook>,
+    pub finish: Option<FinishHook>,
+}
+
 pub struct GameStateHandler {
     event_chan: Sender<StateEvent>,
 }
 impl GameStateHandler {
-    pub fn new(mut state: GameState, sx: Sender<GameState>) -> GameStateHandler {
+    pub fn new(state: GameState, sx: Sender<GameState>) -> GameStateHandler {
+        GameStateHandler::start(state, sx, rand::random(), Hooks::default(), true)
+    }
+
+    /// recorded
+    /// Like `new`, but enemies move from `enemy_seed` and every event is
+    /// passed to `hook`, so the game can be replayed exactly.
+    pub fn recorded(state: GameState, sx: Sender<GameState>, enemy_seed: u64, hook: EventHook) -> GameStateHandler {
+        GameStateHandler::hooked(state, sx, enemy_seed, Hooks { event: Some(hook), finish: None })
+    }
+
+    /// hooked
+    /// Like `new`, but enemies move from `enemy_seed` and `hooks` run on
+    /// the game thread.
+    pub fn hooked(state: GameState, sx: Sender<GameState>, enemy_seed: u64, hooks: Hooks) -> GameStateHandler {
+        GameStateHandler::start(state, sx, enemy_seed, hooks, true)
+    }
+
+    /// replayed
+    /// Plays back `events` at the offsets they were recorded at, instead of
+    /// running the ticker. Ticks come from the recording too.
+    pub fn replayed(
+        state: GameState,
+        sx: Sender<GameState>,
+        enemy_seed: u64,
+        events: Vec<(Duration, StateEvent)>,
+    ) -> GameStateHandler {
+        let handler = GameStateHandler::start(state, sx, enemy_seed, Hooks::default(), false);
+        let tx = handler.get_sender();
+        thread::spawn(move || {
+            let started = Instant::now();
+            for (at, ev) in events {
+                thread::sleep(at.saturating_sub(started.elapsed()));
+                if tx.send(ev).is_err() {
+                    break;
+                }
+            }
+        });
+        handler
+    }
+
+    fn start(
+        mut state: GameState,
+        sx: Sender<GameState>,
+        enemy_seed: u64,
+        hooks: Hooks,
+        ticker: bool,
+    ) -> GameStateHandler {
         let (tx, rx) = mpsc::channel();
-        thread::spawn(move || state.listen(rx, sx));
+        let solve_tx = tx.clone();
+        thread::spawn(move || {
+            let mut rng = StdRng::seed_from_u64(enemy_seed);
+            state.listen(rx, solve_tx, sx, &mut rng, hooks)
+        });
+
+        if ticker {
+            let tick_tx = tx.clone();
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(TICK);
+                    if tick_tx.send(StateEvent::Tick).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
         GameStateHandler {
             event_chan: tx,
         }
@@ -174,18 +1720,16 @@ impl GameStateHandler {
 /// convert_generated_maze
 pub fn convert_generated_maze(generated: Vec<Vec<BoardCell>>) -> Vec<Vec<char>> {
     // Convert Board into render board.
-    let render_size = (2 * generated.len()) - 1;
-    let mut render_board = vec![vec![' '; render_size]; render_size];
-
-    for y in 0..generated.len() {
-        for x in 0..generated.len() {
-            let c = generated[y][x];
+    let (gen_width, gen_height) = (generated[0].len(), generated.len());
+    let mut render_board = vec![vec![' '; (2 * gen_width) - 1]; (2 * gen_height) - 1];
 
+    for (y, row) in generated.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
             let ry = 2*y;
             let rx = 2*x;
 
-            if y < generated.len()-1 {
-                if x < generated.len()-1 {
+            if y < gen_height-1 {
+                if x < gen_width-1 {
                     if c.wall_bottom {
                         render_board[ry+1][rx] = WALL;
                     }
@@ -1,27 +1,308 @@
 ///
 /// Generate Maze Module
 ///
-use rand::Rng;
+mod braid;
+mod division;
+mod eller;
+mod kruskal;
+mod prim;
+mod torus;
+mod wilson;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
+use std::fmt;
 
 use super::{BoardCell, Position};
 
+pub use self::braid::{braid, dead_ends};
+pub use self::division::generate_maze_recursive_division;
+pub use self::eller::generate_maze_eller;
+pub use self::kruskal::generate_maze_kruskal;
+pub use self::prim::generate_maze_prim;
+pub use self::torus::{convert_wrapped_maze, generate_maze_torus};
+pub use self::wilson::generate_maze_wilson;
+
+/// MazeAlgorithm
+/// The generation strategies available to `generate_maze_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MazeAlgorithm {
+    /// Randomized depth first search, long winding corridors.
+    #[default]
+    RecursiveBacktracker,
+    /// Randomized Prim's, more branching and short dead ends.
+    Prim,
+    /// Randomized Kruskal's, merges disjoint sets across random walls.
+    Kruskal,
+    /// Wilson's loop-erased random walks, an unbiased uniform spanning tree.
+    Wilson,
+    /// Eller's row by row generation, a single row of state at a time.
+    Eller,
+    /// Recursive division, walls added to an open board with one gap each.
+    RecursiveDivision,
+}
+
+impl MazeAlgorithm {
+    /// Every algorithm, in menu order.
+    pub const ALL: [MazeAlgorithm; 6] = [
+        MazeAlgorithm::RecursiveBacktracker,
+        MazeAlgorithm::Prim,
+        MazeAlgorithm::Kruskal,
+        MazeAlgorithm::Wilson,
+        MazeAlgorithm::Eller,
+        MazeAlgorithm::RecursiveDivision,
+    ];
+}
+
+impl fmt::Display for MazeAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            MazeAlgorithm::RecursiveBacktracker => "Recursive Backtracker",
+            MazeAlgorithm::Prim => "Prim",
+            MazeAlgorithm::Kruskal => "Kruskal",
+            MazeAlgorithm::Wilson => "Wilson",
+            MazeAlgorithm::Eller => "Eller",
+            MazeAlgorithm::RecursiveDivision => "Recursive Division",
+        })
+    }
+}
+
+/// Difficulty
+/// Presets for maze size and how much of the maze is braided into loops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// A small maze with many dead ends opened up.
+    Easy,
+    #[default]
+    Medium,
+    /// A large perfect maze, every dead end left in place.
+    Hard,
+}
+
+impl Difficulty {
+    /// size
+    /// Render board width and height for the preset.
+    pub fn size(&self) -> usize {
+        match self {
+            Difficulty::Easy => 11,
+            Difficulty::Medium => 21,
+            Difficulty::Hard => 41,
+        }
+    }
+
+    /// braid_factor
+    /// Chance of opening each dead end, see `braid`.
+    pub fn braid_factor(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Medium => 0.2,
+            Difficulty::Hard => 0.0,
+        }
+    }
+}
+
+impl std::str::FromStr for MazeAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MazeAlgorithm, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "backtracker" | "recursive-backtracker" => Ok(MazeAlgorithm::RecursiveBacktracker),
+            "prim" => Ok(MazeAlgorithm::Prim),
+            "kruskal" => Ok(MazeAlgorithm::Kruskal),
+            "wilson" => Ok(MazeAlgorithm::Wilson),
+            "eller" => Ok(MazeAlgorithm::Eller),
+            "division" | "recursive-division" => Ok(MazeAlgorithm::RecursiveDivision),
+            _ => Err(format!("unknown algorithm {s:?}, expected backtracker, prim, kruskal, wilson, eller or division")),
+        }
+    }
+}
+
+impl Difficulty {
+    /// Every difficulty, in menu order.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        })
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("unknown difficulty {s:?}, expected easy, medium or hard")),
+        }
+    }
+}
+
 /// generate_maze
 pub fn generate_maze(board_size: usize) -> Vec<Vec<BoardCell>> {
-    let gen_size = (board_size / 2) + 1;
+    generate_maze_sized(board_size, board_size)
+}
+
+/// generate_maze_sized
+/// Accepts the render board width and height, which may differ.
+pub fn generate_maze_sized(width: usize, height: usize) -> Vec<Vec<BoardCell>> {
+    generate_maze_with_rng(width, height, &mut rand::thread_rng())
+}
+
+/// generate_maze_seeded
+/// Same seed and board size always produce the same maze.
+pub fn generate_maze_seeded(board_size: usize, seed: u64) -> Vec<Vec<BoardCell>> {
+    generate_maze_with_rng(board_size, board_size, &mut StdRng::seed_from_u64(seed))
+}
+
+/// generate_maze_with_rng
+/// Generates with the default algorithm, drawing every choice from `rng`.
+pub fn generate_maze_with_rng<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    generate_maze_with(MazeAlgorithm::default(), width, height, rng)
+}
+
+/// generate_maze_with
+/// Dispatches to the generator for `algo`.
+/// The returned grid is indexed `[y][x]`.
+pub fn generate_maze_with<R: Rng>(algo: MazeAlgorithm, width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    generate_maze_observed(algo, width, height, rng, &mut |_| ())
+}
+
+/// generate_maze_observed
+/// Like `generate_maze_with`, passing the partly generated grid to
+/// `observe` after each step. What a step is depends on the algorithm,
+/// e.g. one wall for Kruskal's or one row for Eller's. The carving
+/// generators knock walls down, while the backtracker and recursive
+/// division put them up on an open grid.
+pub fn generate_maze_observed<R: Rng>(
+    algo: MazeAlgorithm,
+    width: usize,
+    height: usize,
+    rng: &mut R,
+    observe: &mut dyn FnMut(&[Vec<BoardCell>]),
+) -> Vec<Vec<BoardCell>> {
+    match algo {
+        MazeAlgorithm::RecursiveBacktracker => backtracker_observed(width, height, rng, observe),
+        MazeAlgorithm::Prim => prim::observed(width, height, rng, observe),
+        MazeAlgorithm::Kruskal => kruskal::observed(width, height, rng, observe),
+        MazeAlgorithm::Wilson => wilson::observed(width, height, rng, observe),
+        MazeAlgorithm::Eller => eller::observed(width, height, rng, observe),
+        MazeAlgorithm::RecursiveDivision => division::observed(width, height, rng, observe),
+    }
+}
+
+/// generate_maze_steps
+/// Every intermediate grid `generate_maze_observed` passes on, ending
+/// with the finished maze.
+pub fn generate_maze_steps<R: Rng>(algo: MazeAlgorithm, width: usize, height: usize, rng: &mut R) -> Vec<Vec<Vec<BoardCell>>> {
+    let mut steps = vec![];
+    let board = generate_maze_observed(algo, width, height, rng, &mut |step| steps.push(step.to_vec()));
+    steps.push(board);
+    steps
+}
+
+/// gen_dims
+/// Generation grid dimensions for a render board of `width` x `height`.
+/// Every generation cell becomes the render cell at `(2x, 2y)`, and the odd
+/// rows and columns in between hold the walls, so a `gen` wide grid renders
+/// `2 * gen - 1` wide. Generators must only count generation cells.
+fn gen_dims(width: usize, height: usize) -> (usize, usize) {
+    ((width / 2) + 1, (height / 2) + 1)
+}
+
+/// render_dims
+/// The render board dimensions actually generated for a requested size.
+pub fn render_dims(width: usize, height: usize) -> (usize, usize) {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    ((2 * gen_width) - 1, (2 * gen_height) - 1)
+}
+
+/// walled_grid
+/// A generation grid with every interior wall standing, for carving
+/// algorithms. Walls on the outer edge are left off, the border covers them.
+fn walled_grid(gen_width: usize, gen_height: usize) -> Vec<Vec<BoardCell>> {
+    let mut board = vec![vec![BoardCell {
+        wall_right: true,
+        wall_bottom: true,
+    }; gen_width]; gen_height];
+    for row in board.iter_mut() {
+        row[gen_width - 1].wall_right = false;
+    }
+    for c in board[gen_height - 1].iter_mut() {
+        c.wall_bottom = false;
+    }
+    board
+}
+
+/// remove_wall
+/// Opens the wall between two orthogonally adjacent cells.
+fn remove_wall(board: &mut [Vec<BoardCell>], a: Position, b: Position) {
+    let (first, second) = if (a.y, a.x) < (b.y, b.x) { (a, b) } else { (b, a) };
+    if first.y == second.y {
+        board[first.y][first.x].wall_right = false;
+    } else {
+        board[first.y][first.x].wall_bottom = false;
+    }
+}
+
+/// grid_neighbors
+/// Orthogonally adjacent cells of `pos` within a generation grid.
+fn grid_neighbors(pos: Position, gen_width: usize, gen_height: usize) -> Vec<Position> {
+    let mut neighbors = vec![];
+    if pos.y > 0 {
+        neighbors.push(Position { x: pos.x, y: pos.y - 1 });
+    }
+    if pos.x > 0 {
+        neighbors.push(Position { x: pos.x - 1, y: pos.y });
+    }
+    if pos.y < gen_height - 1 {
+        neighbors.push(Position { x: pos.x, y: pos.y + 1 });
+    }
+    if pos.x < gen_width - 1 {
+        neighbors.push(Position { x: pos.x + 1, y: pos.y });
+    }
+    neighbors
+}
+
+/// generate_maze_backtracker
+/// Randomized depth first search (recursive backtracker).
+pub fn generate_maze_backtracker<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    backtracker_observed(width, height, rng, &mut |_| ())
+}
+
+/// backtracker_observed
+/// `generate_maze_backtracker`, passing the board to `observe` after each
+/// step of the walk.
+fn backtracker_observed<R: Rng>(
+    width: usize,
+    height: usize,
+    rng: &mut R,
+    observe: &mut dyn FnMut(&[Vec<BoardCell>]),
+) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
     let mut board = vec![vec![BoardCell {
         wall_right: false,
         wall_bottom: false,
-    }; gen_size]; gen_size];
+    }; gen_width]; gen_height];
     let mut pos = Position {
-        y: gen_size / 2,
-        x: gen_size / 2,
+        y: gen_height / 2,
+        x: gen_width / 2,
     };
     let mut visited = HashSet::new();
     let mut stack = vec![pos];
 
     let mut popped = false;
-    while stack.len() > 0 && visited.len() < board_size.pow(2) {
+    let cell_count = gen_width * gen_height;
+    while !stack.is_empty() && visited.len() < cell_count {
         visited.insert(pos);
 
         let mut moves = vec![];
@@ -50,7 +331,7 @@ pub fn generate_maze(board_size: usize) -> Vec<Vec<BoardCell>> {
             }
         }
         // Move Down
-        if pos.y < gen_size - 1 {
+        if pos.y < gen_height - 1 {
             let mv = Position {
                 y: pos.y + 1,
                 x: pos.x,
@@ -62,7 +343,7 @@ pub fn generate_maze(board_size: usize) -> Vec<Vec<BoardCell>> {
             }
         }
         // Move Right
-        if pos.x < gen_size - 1 {
+        if pos.x < gen_width - 1 {
             let mv = Position {
                 y: pos.y,
                 x: pos.x + 1,
@@ -74,17 +355,17 @@ pub fn generate_maze(board_size: usize) -> Vec<Vec<BoardCell>> {
             }
         }
 
-        if moves.len() > 0 {
+        if !moves.is_empty() {
             stack.push(pos);
             popped = false;
             // Choose randomly where to move.
-            let mut rng = rand::thread_rng();
             let move_idx = rng.gen_range(0..moves.len());
             pos = moves[move_idx];
         } else {
             pos = stack.pop().unwrap();
             popped = true;
         }
+        observe(&board);
     }
     board
 }
@@ -0,0 +1,78 @@
+///
+/// Maze Braiding
+///
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::game_state::{Position, WALL};
+
+/// braid
+/// Knocks out walls at dead ends of a render board to create loops.
+/// Each dead end is opened with probability `factor`, so 0.0 leaves the maze
+/// perfect and 1.0 removes every dead end. When a dead end has a choice of
+/// walls, one leading into another dead end is preferred. A `factor` that
+/// isn't finite counts as 0.0.
+pub fn braid<R: Rng>(board: &mut [Vec<char>], factor: f64, rng: &mut R) {
+    if !factor.is_finite() {
+        return;
+    }
+    let mut dead_ends = dead_ends(board);
+    dead_ends.shuffle(rng);
+
+    for cell in dead_ends {
+        // An earlier knock out may already have joined this one up.
+        if open_sides(board, cell).len() != 1 || !rng.gen_bool(factor.clamp(0.0, 1.0)) {
+            continue;
+        }
+        let mut walls = closed_sides(board, cell);
+        walls.shuffle(rng);
+        let prefer = walls.iter().position(|&(_, beyond)| open_sides(board, beyond).len() == 1);
+        if let Some(&(wall, _)) = prefer.map(|i| &walls[i]).or(walls.first()) {
+            board[wall.y()][wall.x()] = ' ';
+        }
+    }
+}
+
+/// dead_ends
+/// Render cells with exactly one open side.
+pub fn dead_ends(board: &[Vec<char>]) -> Vec<Position> {
+    let mut out = vec![];
+    for y in (0..board.len()).step_by(2) {
+        for x in (0..board[y].len()).step_by(2) {
+            let pos = Position::new(x, y);
+            if open_sides(board, pos).len() == 1 {
+                out.push(pos);
+            }
+        }
+    }
+    out
+}
+
+/// sides
+/// For each in bounds direction, the wall slot next to `cell` and the cell
+/// beyond it, as `(wall, beyond)`.
+fn sides(board: &[Vec<char>], cell: Position) -> Vec<(Position, Position)> {
+    let (x, y) = (cell.x(), cell.y());
+    let mut out = vec![];
+    if y >= 2 {
+        out.push((Position::new(x, y - 1), Position::new(x, y - 2)));
+    }
+    if x >= 2 {
+        out.push((Position::new(x - 1, y), Position::new(x - 2, y)));
+    }
+    if y + 2 < board.len() {
+        out.push((Position::new(x, y + 1), Position::new(x, y + 2)));
+    }
+    if x + 2 < board[y].len() {
+        out.push((Position::new(x + 1, y), Position::new(x + 2, y)));
+    }
+    out
+}
+
+fn open_sides(board: &[Vec<char>], cell: Position) -> Vec<(Position, Position)> {
+    sides(board, cell).into_iter().filter(|(w, _)| board[w.y()][w.x()] != WALL).collect()
+}
+
+fn closed_sides(board: &[Vec<char>], cell: Position) -> Vec<(Position, Position)> {
+    sides(board, cell).into_iter().filter(|(w, _)| board[w.y()][w.x()] == WALL).collect()
+}
@@ -0,0 +1,73 @@
+///
+/// Recursive Division Algorithm
+///
+use rand::Rng;
+
+use super::gen_dims;
+use crate::game_state::BoardCell;
+
+/// Chambers narrower than this along an axis aren't split across it.
+/// Walls go between cells, so the smallest chamber is a 1 cell corridor.
+const MIN_CHAMBER: usize = 2;
+
+/// Chamber
+/// A rectangle of generation cells still to be divided.
+#[derive(Debug, Clone, Copy)]
+struct Chamber {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// generate_maze_recursive_division
+/// Starts from an open board and splits it with a wall that has a single
+/// gap, then splits each side again until the chambers are corridors.
+/// Wide chambers are split vertically and tall ones horizontally, square
+/// ones either way, which gives long straight walls unlike the carving
+/// generators.
+pub fn generate_maze_recursive_division<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    observed(width, height, rng, &mut |_| ())
+}
+
+/// observed
+/// `generate_maze_recursive_division`, passing the board to `observe`
+/// after each wall.
+pub(super) fn observed<R: Rng>(width: usize, height: usize, rng: &mut R, observe: &mut dyn FnMut(&[Vec<BoardCell>])) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = vec![vec![BoardCell { wall_right: false, wall_bottom: false }; gen_width]; gen_height];
+    let mut chambers = vec![Chamber { x: 0, y: 0, width: gen_width, height: gen_height }];
+
+    while let Some(c) = chambers.pop() {
+        let (can_split_x, can_split_y) = (c.width >= MIN_CHAMBER, c.height >= MIN_CHAMBER);
+        let vertical = match (can_split_x, can_split_y) {
+            (false, false) => continue,
+            (true, false) => true,
+            (false, true) => false,
+            _ if c.width != c.height => c.width > c.height,
+            _ => rng.gen_bool(0.5),
+        };
+
+        if vertical {
+            // Wall to the right of column `wall`, open at row `gap`.
+            let wall = c.x + rng.gen_range(0..c.width - 1);
+            let gap = c.y + rng.gen_range(0..c.height);
+            for y in (c.y..c.y + c.height).filter(|&y| y != gap) {
+                board[y][wall].wall_right = true;
+            }
+            chambers.push(Chamber { width: wall - c.x + 1, ..c });
+            chambers.push(Chamber { x: wall + 1, width: c.x + c.width - wall - 1, ..c });
+        } else {
+            // Wall below row `wall`, open at column `gap`.
+            let wall = c.y + rng.gen_range(0..c.height - 1);
+            let gap = c.x + rng.gen_range(0..c.width);
+            for x in (c.x..c.x + c.width).filter(|&x| x != gap) {
+                board[wall][x].wall_bottom = true;
+            }
+            chambers.push(Chamber { height: wall - c.y + 1, ..c });
+            chambers.push(Chamber { y: wall + 1, height: c.y + c.height - wall - 1, ..c });
+        }
+        observe(&board);
+    }
+    board
+}
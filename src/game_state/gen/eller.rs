@@ -0,0 +1,72 @@
+///
+/// Eller's Algorithm
+///
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{gen_dims, walled_grid};
+use crate::game_state::BoardCell;
+
+/// generate_maze_eller
+/// Builds the maze one row at a time, only tracking which set each cell
+/// of the current row belongs to. Neighbors in different sets are joined
+/// at random, then every set drops at least one passage into the next row
+/// so nothing is cut off. The last row joins whatever sets are left.
+/// Only the returned board grows with the height, the generator itself
+/// needs memory for a single row.
+pub fn generate_maze_eller<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    observed(width, height, rng, &mut |_| ())
+}
+
+/// observed
+/// `generate_maze_eller`, passing the board to `observe` after each row.
+/// Watching needs the whole board, unlike generating.
+pub(super) fn observed<R: Rng>(width: usize, height: usize, rng: &mut R, observe: &mut dyn FnMut(&[Vec<BoardCell>])) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = walled_grid(gen_width, gen_height);
+    let mut sets: Vec<Option<usize>> = vec![None; gen_width];
+    let mut next_set = 0;
+
+    for y in 0..gen_height {
+        let last_row = y == gen_height - 1;
+        let mut row_sets: Vec<usize> = sets.iter()
+            .map(|set| set.unwrap_or_else(|| {
+                next_set += 1;
+                next_set
+            }))
+            .collect();
+
+        // Join neighbors, always on the last row so every set meets.
+        for x in 0..gen_width - 1 {
+            let (left, right) = (row_sets[x], row_sets[x + 1]);
+            if left != right && (last_row || rng.gen_bool(0.5)) {
+                board[y][x].wall_right = false;
+                for set in row_sets.iter_mut().filter(|s| **s == right) {
+                    *set = left;
+                }
+            }
+        }
+        if last_row {
+            break;
+        }
+
+        // Drop at least one passage from every set into the next row.
+        let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (x, &set) in row_sets.iter().enumerate() {
+            members.entry(set).or_default().push(x);
+        }
+        sets = vec![None; gen_width];
+        for (set, mut xs) in members {
+            xs.shuffle(rng);
+            let drops = rng.gen_range(1..=xs.len());
+            for &x in &xs[..drops] {
+                board[y][x].wall_bottom = false;
+                sets[x] = Some(set);
+            }
+        }
+        observe(&board);
+    }
+    board
+}
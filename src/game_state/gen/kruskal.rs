@@ -0,0 +1,87 @@
+///
+/// Randomized Kruskal's Algorithm
+///
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{gen_dims, remove_wall, walled_grid};
+use crate::game_state::{BoardCell, Position};
+
+/// DisjointSet
+/// Union-find over cell indices with path compression and union by rank.
+struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..size).collect(),
+            rank: vec![0; size],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        if self.parent[i] != i {
+            self.parent[i] = self.find(self.parent[i]);
+        }
+        self.parent[i]
+    }
+
+    /// union
+    /// Merges the sets holding `a` and `b`.
+    /// Returns false if they were already in the same set.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        match self.rank[ra].cmp(&self.rank[rb]) {
+            std::cmp::Ordering::Less => self.parent[ra] = rb,
+            std::cmp::Ordering::Greater => self.parent[rb] = ra,
+            std::cmp::Ordering::Equal => {
+                self.parent[rb] = ra;
+                self.rank[ra] += 1;
+            }
+        }
+        true
+    }
+}
+
+/// generate_maze_kruskal
+/// Visits every interior wall in random order, knocking it down whenever
+/// the cells on either side are not yet connected.
+pub fn generate_maze_kruskal<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    observed(width, height, rng, &mut |_| ())
+}
+
+/// observed
+/// `generate_maze_kruskal`, passing the board to `observe` after each step.
+pub(super) fn observed<R: Rng>(width: usize, height: usize, rng: &mut R, observe: &mut dyn FnMut(&[Vec<BoardCell>])) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = walled_grid(gen_width, gen_height);
+    let mut sets = DisjointSet::new(gen_width * gen_height);
+
+    let mut edges = vec![];
+    for y in 0..gen_height {
+        for x in 0..gen_width {
+            let pos = Position { x, y };
+            if x < gen_width - 1 {
+                edges.push((pos, Position { x: x + 1, y }));
+            }
+            if y < gen_height - 1 {
+                edges.push((pos, Position { x, y: y + 1 }));
+            }
+        }
+    }
+    edges.shuffle(rng);
+
+    for (a, b) in edges {
+        if sets.union(a.y * gen_width + a.x, b.y * gen_width + b.x) {
+            remove_wall(&mut board, a, b);
+            observe(&board);
+        }
+    }
+    board
+}
@@ -0,0 +1,53 @@
+///
+/// Randomized Prim's Algorithm
+///
+use rand::Rng;
+
+use super::{gen_dims, grid_neighbors, remove_wall, walled_grid};
+use crate::game_state::{BoardCell, Position};
+
+/// generate_maze_prim
+/// Grows the maze outward from a random cell, each step joining a random
+/// frontier cell to a random neighbor already in the maze.
+pub fn generate_maze_prim<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    observed(width, height, rng, &mut |_| ())
+}
+
+/// observed
+/// `generate_maze_prim`, passing the board to `observe` after each step.
+pub(super) fn observed<R: Rng>(width: usize, height: usize, rng: &mut R, observe: &mut dyn FnMut(&[Vec<BoardCell>])) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = walled_grid(gen_width, gen_height);
+    let mut in_maze = vec![vec![false; gen_width]; gen_height];
+    let mut in_frontier = vec![vec![false; gen_width]; gen_height];
+    let mut frontier = vec![];
+
+    let start = Position {
+        x: rng.gen_range(0..gen_width),
+        y: rng.gen_range(0..gen_height),
+    };
+    in_maze[start.y][start.x] = true;
+    for n in grid_neighbors(start, gen_width, gen_height) {
+        in_frontier[n.y][n.x] = true;
+        frontier.push(n);
+    }
+
+    while !frontier.is_empty() {
+        let pos = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+
+        let mut connected = vec![];
+        for n in grid_neighbors(pos, gen_width, gen_height) {
+            if in_maze[n.y][n.x] {
+                connected.push(n);
+            } else if !in_frontier[n.y][n.x] {
+                in_frontier[n.y][n.x] = true;
+                frontier.push(n);
+            }
+        }
+        let link = connected[rng.gen_range(0..connected.len())];
+        remove_wall(&mut board, pos, link);
+        in_maze[pos.y][pos.x] = true;
+        observe(&board);
+    }
+    board
+}
@@ -0,0 +1,99 @@
+///
+/// Toroidal Maze Module
+///
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::gen_dims;
+use crate::game_state::{BoardCell, Position, WALL};
+
+/// generate_maze_torus
+/// Recursive backtracker over a grid whose edges wrap, so passages can
+/// leave one side and come back on the other. Unlike the other generators
+/// every cell keeps its right and bottom wall slots, the last column and
+/// row of them form the seam. Axes under 3 cells don't wrap.
+pub fn generate_maze_torus<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = vec![vec![BoardCell { wall_right: true, wall_bottom: true }; gen_width]; gen_height];
+    let mut visited = vec![vec![false; gen_width]; gen_height];
+    let start = Position { x: 0, y: 0 };
+    visited[0][0] = true;
+    let mut stack = vec![start];
+
+    while let Some(&pos) = stack.last() {
+        let mut moves: Vec<(Position, Side)> = torus_neighbors(pos, gen_width, gen_height)
+            .filter(|(n, _)| !visited[n.y][n.x])
+            .collect();
+        moves.shuffle(rng);
+        match moves.first() {
+            Some(&(next, side)) => {
+                open(&mut board, pos, next, side);
+                visited[next.y][next.x] = true;
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    board
+}
+
+/// convert_wrapped_maze
+/// Renders a toroidal grid as a `2 * gen` wide and high board, one wall
+/// slot wider and taller than `convert_generated_maze` so the seam walls
+/// have somewhere to go.
+pub fn convert_wrapped_maze(generated: Vec<Vec<BoardCell>>) -> Vec<Vec<char>> {
+    let (gen_width, gen_height) = (generated[0].len(), generated.len());
+    let mut board = vec![vec![' '; 2 * gen_width]; 2 * gen_height];
+    for (y, row) in generated.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            if c.wall_right {
+                board[2 * y][2 * x + 1] = WALL;
+            }
+            if c.wall_bottom {
+                board[2 * y + 1][2 * x] = WALL;
+            }
+            board[2 * y + 1][2 * x + 1] = WALL;
+        }
+    }
+    board
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// torus_neighbors
+/// Adjacent cells of `pos`, wrapping around axes of at least 3 cells.
+fn torus_neighbors(pos: Position, gen_width: usize, gen_height: usize) -> impl Iterator<Item = (Position, Side)> {
+    let (x, y) = (pos.x, pos.y);
+    let (wrap_x, wrap_y) = (gen_width >= 3, gen_height >= 3);
+    let up = if y > 0 { Some(y - 1) } else { wrap_y.then(|| gen_height - 1) };
+    let down = if y + 1 < gen_height { Some(y + 1) } else { wrap_y.then_some(0) };
+    let left = if x > 0 { Some(x - 1) } else { wrap_x.then(|| gen_width - 1) };
+    let right = if x + 1 < gen_width { Some(x + 1) } else { wrap_x.then_some(0) };
+    [
+        up.map(|y| (Position { x, y }, Side::Up)),
+        down.map(|y| (Position { x, y }, Side::Down)),
+        left.map(|x| (Position { x, y }, Side::Left)),
+        right.map(|x| (Position { x, y }, Side::Right)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// open
+/// Removes the wall on `side` of `pos`, which leads to `next`.
+fn open(board: &mut [Vec<BoardCell>], pos: Position, next: Position, side: Side) {
+    match side {
+        Side::Right => board[pos.y][pos.x].wall_right = false,
+        Side::Left => board[next.y][next.x].wall_right = false,
+        Side::Down => board[pos.y][pos.x].wall_bottom = false,
+        Side::Up => board[next.y][next.x].wall_bottom = false,
+    }
+}
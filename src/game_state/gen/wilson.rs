@@ -0,0 +1,54 @@
+///
+/// Wilson's Algorithm
+///
+use rand::Rng;
+
+use super::{gen_dims, grid_neighbors, remove_wall, walled_grid};
+use crate::game_state::{BoardCell, Position};
+
+/// generate_maze_wilson
+/// Loop-erased random walks from each cell outside the maze until they hit
+/// it, then carves the walk in. Every spanning tree is equally likely, so
+/// unlike the backtracker's long corridors the maze has no bias.
+pub fn generate_maze_wilson<R: Rng>(width: usize, height: usize, rng: &mut R) -> Vec<Vec<BoardCell>> {
+    observed(width, height, rng, &mut |_| ())
+}
+
+/// observed
+/// `generate_maze_wilson`, passing the board to `observe` after each walk.
+pub(super) fn observed<R: Rng>(width: usize, height: usize, rng: &mut R, observe: &mut dyn FnMut(&[Vec<BoardCell>])) -> Vec<Vec<BoardCell>> {
+    let (gen_width, gen_height) = gen_dims(width, height);
+    let mut board = walled_grid(gen_width, gen_height);
+    let mut in_maze = vec![vec![false; gen_width]; gen_height];
+    // Where the current walk last left each cell. Revisiting a cell
+    // overwrites its exit, which erases the loop in between.
+    let mut exit: Vec<Vec<Option<Position>>> = vec![vec![None; gen_width]; gen_height];
+
+    in_maze[rng.gen_range(0..gen_height)][rng.gen_range(0..gen_width)] = true;
+    for y in 0..gen_height {
+        for x in 0..gen_width {
+            let start = Position { x, y };
+            if in_maze[y][x] {
+                continue;
+            }
+
+            let mut pos = start;
+            while !in_maze[pos.y][pos.x] {
+                let neighbors = grid_neighbors(pos, gen_width, gen_height);
+                let next = neighbors[rng.gen_range(0..neighbors.len())];
+                exit[pos.y][pos.x] = Some(next);
+                pos = next;
+            }
+
+            let mut pos = start;
+            while !in_maze[pos.y][pos.x] {
+                in_maze[pos.y][pos.x] = true;
+                let Some(next) = exit[pos.y][pos.x] else { break };
+                remove_wall(&mut board, pos, next);
+                pos = next;
+            }
+            observe(&board);
+        }
+    }
+    board
+}
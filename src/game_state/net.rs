@@ -0,0 +1,127 @@
+///
+/// Network Race Module
+///
+/// Two players race on the same maze over TCP, one line of JSON per
+/// message. The host sends its `GameSetup` first so both sides build the
+/// same maze, after that each side sends where its player is whenever they
+/// move, and a line when they win:
+///
+/// ```text
+/// {"seed":7,"algorithm":"Prim","width":21,...}
+/// {"Moved":{"x":2,"y":0}}
+/// "Won"
+/// ```
+///
+/// Each side plays its own game, the other player is its rival.
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use super::{GameSetup, GameState, Position, StateEvent};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// NetMessage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetMessage {
+    Moved(Position),
+    Won,
+}
+
+impl NetMessage {
+    /// into_state_event
+    pub fn into_state_event(self) -> StateEvent {
+        match self {
+            NetMessage::Moved(pos) => StateEvent::RivalMoved(pos),
+            NetMessage::Won => StateEvent::RivalWon,
+        }
+    }
+}
+
+/// Peer
+/// The connection to the other player and the game both sides play.
+pub struct Peer {
+    pub setup: GameSetup,
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+}
+
+impl Peer {
+    /// host
+    /// Waits on `listener` for a player to join and sends them `setup`.
+    pub fn host(listener: &TcpListener, setup: GameSetup) -> io::Result<Peer> {
+        let (stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        send(&mut BufWriter::new(&stream), &setup)?;
+        Ok(Peer { setup, reader: BufReader::new(stream.try_clone()?), stream })
+    }
+
+    /// join
+    /// Connects to a host and reads the game it's sharing.
+    pub fn join(addr: impl ToSocketAddrs) -> io::Result<Peer> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        // Kept for the rest of the game, it may have read past the setup.
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "the host closed the connection"));
+        }
+        let setup = serde_json::from_str(&line).map_err(io::Error::from)?;
+        Ok(Peer { setup, reader, stream })
+    }
+
+    /// start
+    /// Passes every state from `states` on to `render`, telling the other
+    /// player each time ours moves or wins. Their messages arrive as events
+    /// on `events`, ending with `RivalLeft` when they go.
+    pub fn start(
+        self,
+        states: Receiver<GameState>,
+        render: Sender<GameState>,
+        events: Sender<StateEvent>,
+    ) {
+        let reader = self.reader;
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                let Ok(msg) = serde_json::from_str::<NetMessage>(&line) else {
+                    break;
+                };
+                if events.send(msg.into_state_event()).is_err() {
+                    return;
+                }
+            }
+            let _ = events.send(StateEvent::RivalLeft);
+        });
+
+        let mut out = BufWriter::new(self.stream);
+        thread::spawn(move || {
+            let (mut position, mut won) = (None, false);
+            for state in states {
+                // A lost connection only stops the messages, not the game.
+                if position != Some(state.position) {
+                    position = Some(state.position);
+                    let _ = send(&mut out, &NetMessage::Moved(state.position));
+                }
+                if !won && state.winner == Some(0) {
+                    won = true;
+                    let _ = send(&mut out, &NetMessage::Won);
+                }
+                if render.send(state).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// send
+/// Writes `value` as a line of JSON and flushes it.
+fn send(out: &mut impl Write, value: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, value).map_err(io::Error::from)?;
+    writeln!(out)?;
+    out.flush()
+}
@@ -0,0 +1,167 @@
+///
+/// Replay Module
+///
+/// Recordings are JSON lines. The first line is a `ReplayHeader` holding
+/// the `GameSetup` and the seed enemies move from, every following line is
+/// one event with the milliseconds since the game started:
+///
+/// ```text
+/// {"setup":{"seed":7,"algorithm":"Prim","width":21,...},"enemy_seed":3,"fog":null}
+/// {"at_ms":100,"event":"Tick"}
+/// {"at_ms":240,"event":{"Movement":"RIGHT"}}
+/// ```
+///
+/// Auto-solve is recorded as the moves it makes, saving isn't recorded.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{GameSetup, Movement, StateEvent};
+
+/// ReplayHeader
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub setup: GameSetup,
+    pub enemy_seed: u64,
+    pub fog: Option<usize>,
+    #[serde(default)]
+    pub fog_closing: Option<u64>,
+    #[serde(default)]
+    pub par: Option<usize>,
+    /// Seconds counted down before play began.
+    #[serde(default)]
+    pub countdown: u64,
+}
+
+/// ReplayEvent
+/// The recordable subset of `StateEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    Movement(Movement),
+    Hint,
+    ToggleTrail,
+    ToggleSolution,
+    ToggleHeatMap,
+    Undo,
+    Pause,
+    Tick,
+    Restart,
+    Zoom,
+}
+
+impl ReplayEvent {
+    /// from_state_event
+    /// None for events that can't or shouldn't be replayed.
+    pub fn from_state_event(ev: &StateEvent) -> Option<ReplayEvent> {
+        match ev {
+            StateEvent::Movement(mv) | StateEvent::PlayerMovement(0, mv) => Some(ReplayEvent::Movement(*mv)),
+            StateEvent::Hint => Some(ReplayEvent::Hint),
+            StateEvent::ToggleTrail => Some(ReplayEvent::ToggleTrail),
+            StateEvent::ToggleSolution => Some(ReplayEvent::ToggleSolution),
+            StateEvent::ToggleHeatMap => Some(ReplayEvent::ToggleHeatMap),
+            StateEvent::Undo => Some(ReplayEvent::Undo),
+            StateEvent::Pause => Some(ReplayEvent::Pause),
+            StateEvent::Tick => Some(ReplayEvent::Tick),
+            StateEvent::Restart => Some(ReplayEvent::Restart),
+            StateEvent::Zoom => Some(ReplayEvent::Zoom),
+            _ => None,
+        }
+    }
+
+    /// into_state_event
+    pub fn into_state_event(self) -> StateEvent {
+        match self {
+            ReplayEvent::Movement(mv) => StateEvent::Movement(mv),
+            ReplayEvent::Hint => StateEvent::Hint,
+            ReplayEvent::ToggleTrail => StateEvent::ToggleTrail,
+            ReplayEvent::ToggleSolution => StateEvent::ToggleSolution,
+            ReplayEvent::ToggleHeatMap => StateEvent::ToggleHeatMap,
+            ReplayEvent::Undo => StateEvent::Undo,
+            ReplayEvent::Pause => StateEvent::Pause,
+            ReplayEvent::Tick => StateEvent::Tick,
+            ReplayEvent::Restart => StateEvent::Restart,
+            ReplayEvent::Zoom => StateEvent::Zoom,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    at_ms: u64,
+    event: ReplayEvent,
+}
+
+/// Recorder
+/// Appends each event to a recording as it happens, so a crash still
+/// leaves everything up to it on disk.
+pub struct Recorder {
+    started: Instant,
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    /// create
+    /// Starts a recording at `path`, replacing any file there.
+    pub fn create(path: &Path, header: &ReplayHeader) -> io::Result<Recorder> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, header).map_err(io::Error::from)?;
+        writeln!(out)?;
+        Ok(Recorder { started: Instant::now(), out })
+    }
+
+    /// record
+    /// Logs `ev` if it's replayable.
+    pub fn record(&mut self, ev: &StateEvent) -> io::Result<()> {
+        let Some(event) = ReplayEvent::from_state_event(ev) else {
+            return Ok(());
+        };
+        let entry = Entry { at_ms: self.started.elapsed().as_millis() as u64, event };
+        serde_json::to_writer(&mut self.out, &entry).map_err(io::Error::from)?;
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+/// Replay
+/// A recording read back from disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub events: Vec<(Duration, ReplayEvent)>,
+}
+
+impl Replay {
+    /// load
+    /// Reads a recording written by `Recorder`. Returns an InvalidData
+    /// error naming the line that couldn't be parsed.
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?).map_err(|e| invalid(format!("line 1: {e}")))?,
+            None => return Err(invalid("recording is empty")),
+        };
+        let mut events = vec![];
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(&line).map_err(|e| invalid(format!("line {}: {e}", i + 2)))?;
+            events.push((Duration::from_millis(entry.at_ms), entry.event));
+        }
+        Ok(Replay { header, events })
+    }
+
+    /// state_events
+    /// The recorded events, ready to feed to `GameStateHandler::replayed`.
+    pub fn state_events(&self) -> Vec<(Duration, StateEvent)> {
+        self.events.iter().map(|&(at, ev)| (at, ev.into_state_event())).collect()
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.into())
+}
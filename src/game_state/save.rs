@@ -0,0 +1,162 @@
+///
+/// Save Game Module
+///
+/// Games are stored as JSON objects with these fields:
+///
+/// | field               | type                  | required |
+/// | ------------------- | --------------------- | -------- |
+/// | `board`             | array of strings      | yes      |
+/// | `position`          | `{"x": n, "y": n}`    | yes      |
+/// | `win_position`      | `{"x": n, "y": n}`    | yes      |
+/// | `time_remaining`    | integer seconds       | yes      |
+/// | `goals`             | array of positions    | no       |
+/// | `collected_goals`   | array of positions    | no       |
+/// | `items`             | array of `[position, points]` | no |
+/// | `score`             | integer               | no       |
+/// | `portals`           | array of `[position, position]` | no |
+/// | `one_way`           | array of `[position, direction]` | no |
+/// | `ice`               | array of positions    | no       |
+/// | `costs`             | array of `[position, cost]` | no |
+/// | `diagonals`         | bool                  | no       |
+/// | `hardcore`          | bool                  | no       |
+/// | `enemies`           | array of positions    | no       |
+/// | `victory`           | bool                  | no       |
+/// | `failure`           | bool                  | no       |
+/// | `moves`             | integer               | no       |
+/// | `finished`          | `{"secs", "nanos"}`   | no       |
+/// | `visited`           | array of positions    | no       |
+/// | `show_trail`        | bool                  | no       |
+/// | `visibility_radius` | integer or null       | no       |
+/// | `fog_closing`       | integer seconds or null | no     |
+/// | `facing`            | `"UP"`, `"DOWN"`, `"LEFT"`, `"RIGHT"` or null | no |
+/// | `history`           | array of positions    | no       |
+/// | `message`           | string or null        | no       |
+/// | `wrap`              | bool                  | no       |
+/// | `rival`             | `{"position", "facing", "moves"}` or null | no |
+/// | `winner`            | integer or null       | no       |
+/// | `par`               | integer or null       | no       |
+///
+/// `goals` lists the goals still to collect and defaults to just
+/// `win_position`. A direction is `"UP"`, `"DOWN"`, `"LEFT"` or `"RIGHT"`,
+/// and `winner` is 0 for the first player of a race.
+///
+/// Each board string is one row, one char per cell: `░` wall, space for
+/// open floor, `●` the player and `▓` the goal. Positions are zero based
+/// with `y` counting rows down from the top.
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use super::{GameState, MAX_FOG_CLOSING};
+
+/// board_rows
+/// Serializes the board as one string per row instead of nested char arrays.
+pub mod board_rows {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(board: &[Vec<char>], s: S) -> Result<S::Ok, S::Error> {
+        let rows: Vec<String> = board.iter().map(|row| row.iter().collect()).collect();
+        rows.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Vec<char>>, D::Error> {
+        let rows = Vec::<String>::deserialize(d)?;
+        Ok(rows.iter().map(|row| row.chars().collect()).collect())
+    }
+}
+
+/// position_map
+/// Serializes a map keyed by position as a list of `[position, value]`
+/// pairs, since JSON object keys must be strings.
+pub mod position_map {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::game_state::Position;
+
+    pub fn serialize<S, V>(map: &HashMap<Position, V>, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        let mut pairs: Vec<(&Position, &V)> = map.iter().collect();
+        pairs.sort_by_key(|(p, _)| (p.y(), p.x()));
+        pairs.serialize(s)
+    }
+
+    pub fn deserialize<'de, D, V>(d: D) -> Result<HashMap<Position, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        Ok(Vec::<(Position, V)>::deserialize(d)?.into_iter().collect())
+    }
+}
+
+/// trail_shown
+/// Saves without `show_trail` show it, like a new game.
+pub fn trail_shown() -> bool {
+    true
+}
+
+impl GameState {
+    /// save
+    /// Writes the full game state to `path` as JSON.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, json)
+    }
+
+    /// load
+    /// Reads a game written by `save`, or any JSON matching the schema.
+    /// Returns an InvalidData error if the board is empty, its rows differ
+    /// in length, any stored position, like the player, goal or an enemy,
+    /// lies outside it, or `fog_closing` is out of range.
+    pub fn load(path: &Path) -> io::Result<GameState> {
+        let mut state: GameState = serde_json::from_str(&fs::read_to_string(path)?).map_err(io::Error::from)?;
+
+        let width = state.board.first().map(|row| row.len()).unwrap_or(0);
+        if width == 0 {
+            return Err(invalid("saved board is empty"));
+        }
+        if let Some(y) = state.board.iter().position(|row| row.len() != width) {
+            return Err(invalid(format!(
+                "saved board row {y} is {} cells wide, expected {width}",
+                state.board[y].len()
+            )));
+        }
+        let height = state.board.len();
+        if state.goals.is_empty() && !state.victory {
+            state.goals.push(state.win_position);
+        }
+        let named = [("player", state.position), ("goal", state.win_position)].into_iter()
+            .chain(state.goals.iter().map(|&g| ("goal", g)))
+            .chain(state.collected_goals.iter().map(|&g| ("collected goal", g)))
+            .chain(state.history.iter().map(|&p| ("history", p)))
+            .chain(state.visited.iter().map(|&p| ("visited", p)))
+            .chain(state.items.keys().map(|&p| ("item", p)))
+            .chain(state.enemies.iter().map(|&p| ("enemy", p)))
+            .chain(state.portals.iter().flat_map(|(&from, &to)| [("portal", from), ("portal", to)]))
+            .chain(state.one_way.keys().map(|&p| ("one-way gate", p)))
+            .chain(state.ice.iter().map(|&p| ("ice", p)))
+            .chain(state.costs.keys().map(|&p| ("mud", p)))
+            .chain(state.rival.map(|rival| ("player 2", rival.position)));
+        for (name, pos) in named {
+            if pos.x >= width || pos.y >= height {
+                return Err(invalid(format!(
+                    "saved {name} position ({}, {}) is outside the {width}x{height} board",
+                    pos.x, pos.y
+                )));
+            }
+        }
+        if state.fog_closing.is_some_and(|secs| secs == 0 || secs > MAX_FOG_CLOSING) {
+            return Err(invalid(format!("saved fog_closing must be between 1 and {MAX_FOG_CLOSING} seconds")));
+        }
+        Ok(state)
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.into())
+}
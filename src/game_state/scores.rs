@@ -0,0 +1,106 @@
+///
+/// Leaderboard Module
+///
+/// Best results are kept per maze as a JSON list of records. A maze is
+/// identified by its whole `GameSetup`, so the seed, size and algorithm
+/// plus anything else that changes the game, like braiding or enemies:
+///
+/// ```text
+/// {"records":[{"setup":{"seed":7,"algorithm":"Prim","width":21,...},"time_ms":18250,"moves":96}]}
+/// ```
+///
+/// A faster time beats a record, equal times are broken by fewer moves.
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{GameSetup, GameState};
+use crate::config::config_dir;
+
+/// Score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    pub time_ms: u64,
+    pub moves: usize,
+}
+
+impl Score {
+    /// of
+    /// The time and moves a finished game took.
+    pub fn of(state: &GameState) -> Score {
+        Score { time_ms: state.elapsed().as_millis() as u64, moves: state.moves }
+    }
+
+    /// time
+    pub fn time(&self) -> Duration {
+        Duration::from_millis(self.time_ms)
+    }
+
+    /// beats
+    pub fn beats(&self, other: &Score) -> bool {
+        (self.time_ms, self.moves) < (other.time_ms, other.moves)
+    }
+}
+
+/// Record
+/// The best score on one maze.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub setup: GameSetup,
+    #[serde(flatten)]
+    pub score: Score,
+}
+
+/// Leaderboard
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub records: Vec<Record>,
+}
+
+impl Leaderboard {
+    /// default_path
+    /// `scores.json` in the config directory, see `config::config_dir`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(config_dir()?.join("scores.json"))
+    }
+
+    /// load
+    /// Reads the leaderboard at `path`, a missing file is an empty board.
+    pub fn load(path: &Path) -> io::Result<Leaderboard> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(io::Error::from),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// save
+    /// Writes the leaderboard to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, json)
+    }
+
+    /// best
+    pub fn best(&self, setup: &GameSetup) -> Option<Score> {
+        self.records.iter().find(|r| r.setup == *setup).map(|r| r.score)
+    }
+
+    /// submit
+    /// Keeps `score` if it's the first or best for `setup`, returning
+    /// whether it was kept.
+    pub fn submit(&mut self, setup: GameSetup, score: Score) -> bool {
+        match self.records.iter_mut().find(|r| r.setup == setup) {
+            Some(record) if score.beats(&record.score) => record.score = score,
+            Some(_) => return false,
+            None => self.records.push(Record { setup, score }),
+        }
+        true
+    }
+}
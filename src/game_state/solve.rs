@@ -0,0 +1,421 @@
+///
+/// Solve Maze Module
+///
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use super::{Movement, Position, WALL};
+
+/// SearchAlgorithm
+/// The searches `explore` can show off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchAlgorithm {
+    /// Spreads out evenly, always finding a shortest path.
+    BreadthFirst,
+    /// Heads for the goal, still finding a shortest path.
+    AStar,
+    /// Follows one corridor to its end before backing up, the path it
+    /// finds can be far from shortest.
+    DepthFirst,
+}
+
+impl fmt::Display for SearchAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SearchAlgorithm::BreadthFirst => "bfs",
+            SearchAlgorithm::AStar => "astar",
+            SearchAlgorithm::DepthFirst => "dfs",
+        })
+    }
+}
+
+impl std::str::FromStr for SearchAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SearchAlgorithm, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "bfs" => Ok(SearchAlgorithm::BreadthFirst),
+            "astar" | "a*" => Ok(SearchAlgorithm::AStar),
+            "dfs" => Ok(SearchAlgorithm::DepthFirst),
+            _ => Err(format!("unknown search {s:?}, expected bfs, astar or dfs")),
+        }
+    }
+}
+
+/// solve
+/// Breadth first search from `start` to `goal` over every non wall cell.
+/// Returns the shortest path including both endpoints, or None if the
+/// goal can't be reached.
+pub fn solve(board: &[Vec<char>], start: Position, goal: Position) -> Option<Vec<Position>> {
+    solve_nearest(board, start, &[goal])
+}
+
+/// solve_nearest
+/// Shortest path from `start` to whichever of `goals` is closest.
+pub fn solve_nearest(board: &[Vec<char>], start: Position, goals: &[Position]) -> Option<Vec<Position>> {
+    solve_nearest_by(board, start, goals, neighbors)
+}
+
+/// solve_nearest_wrapping
+/// Like `solve_nearest`, on a board whose edges wrap around.
+pub fn solve_nearest_wrapping(board: &[Vec<char>], start: Position, goals: &[Position]) -> Option<Vec<Position>> {
+    solve_nearest_by(board, start, goals, wrapping_neighbors)
+}
+
+/// solve_nearest_through
+/// Like `solve_nearest`, where stepping on one of `portals` jumps to its
+/// partner and `one_way` gates can only be crossed in their direction.
+/// The path includes both the portal and where it lands, two cells that
+/// aren't adjacent.
+pub fn solve_nearest_through(
+    board: &[Vec<char>],
+    start: Position,
+    goals: &[Position],
+    portals: &HashMap<Position, Position>,
+    one_way: &HashMap<Position, Movement>,
+    wrap: bool,
+) -> Option<Vec<Position>> {
+    let neighbors = if wrap { wrapping_neighbors } else { neighbors };
+    solve_nearest_via(board, start, goals, neighbors, portals, one_way)
+}
+
+fn solve_nearest_by(
+    board: &[Vec<char>],
+    start: Position,
+    goals: &[Position],
+    neighbors: fn(&[Vec<char>], Position) -> Vec<Position>,
+) -> Option<Vec<Position>> {
+    solve_nearest_via(board, start, goals, neighbors, &HashMap::new(), &HashMap::new())
+}
+
+fn solve_nearest_via(
+    board: &[Vec<char>],
+    start: Position,
+    goals: &[Position],
+    neighbors: fn(&[Vec<char>], Position) -> Vec<Position>,
+    portals: &HashMap<Position, Position>,
+    one_way: &HashMap<Position, Movement>,
+) -> Option<Vec<Position>> {
+    let dims = (board[0].len(), board.len());
+    let crossable = |from: Position, to: Position| {
+        let dir = Movement::between_wrapping(from, to, dims);
+        [from, to].iter().all(|p| one_way.get(p).is_none_or(|&gate| Some(gate) == dir))
+    };
+    let mut came_from = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    came_from.insert(start, start);
+
+    while let Some(pos) = queue.pop_front() {
+        if goals.contains(&pos) {
+            let mut path = vec![pos];
+            let mut cur = pos;
+            while cur != start {
+                cur = came_from[&cur];
+                path.push(cur);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in neighbors(board, pos).into_iter().filter(|&next| crossable(pos, next)) {
+            if let Entry::Vacant(e) = came_from.entry(next) {
+                e.insert(pos);
+                // Nobody stands on a portal, they land on its partner.
+                match portals.get(&next) {
+                    Some(&partner) => if let Entry::Vacant(e) = came_from.entry(partner) {
+                        e.insert(next);
+                        queue.push_back(partner);
+                    },
+                    None => queue.push_back(next),
+                }
+            }
+        }
+    }
+    None
+}
+
+/// solve_astar
+/// Shortest path from `start` to `goal` by A* search, guided by
+/// `heuristic`'s guess at the steps between two cells. The path is only
+/// guaranteed shortest if the guess never overestimates, as `manhattan`
+/// and `euclidean` don't.
+pub fn solve_astar(
+    board: &[Vec<char>],
+    start: Position,
+    goal: Position,
+    heuristic: fn(Position, Position) -> u32,
+) -> Option<Vec<Position>> {
+    astar(board, start, &[goal], &HashMap::new(), neighbors, |pos| u64::from(heuristic(pos, goal)))
+}
+
+/// solve_dfs
+/// A path from `start` to `goal` by depth first search, which follows
+/// each corridor to its end before backing up. Often far from shortest.
+pub fn solve_dfs(board: &[Vec<char>], start: Position, goal: Position) -> Option<Vec<Position>> {
+    explore(board, start, goal, SearchAlgorithm::DepthFirst, |_, _| ())
+}
+
+/// manhattan
+/// Steps between `a` and `b` with nothing in the way.
+pub fn manhattan(a: Position, b: Position) -> u32 {
+    (a.x.abs_diff(b.x) + a.y.abs_diff(b.y)) as u32
+}
+
+/// euclidean
+/// The straight line distance between `a` and `b`, rounded down.
+pub fn euclidean(a: Position, b: Position) -> u32 {
+    let (dx, dy) = (a.x.abs_diff(b.x) as f64, a.y.abs_diff(b.y) as f64);
+    dx.hypot(dy) as u32
+}
+
+/// solve_weighted
+/// Cheapest path from `start` to whichever of `goals` costs least to
+/// reach, where entering a cell costs its weight in `costs` and 1 if it
+/// has none. A* search, guided by the fewest steps left to the nearest
+/// goal, which never overestimates as no step costs less than 1.
+pub fn solve_weighted(
+    board: &[Vec<char>],
+    start: Position,
+    goals: &[Position],
+    costs: &HashMap<Position, u32>,
+    wrap: bool,
+) -> Option<Vec<Position>> {
+    let (width, height) = (board[0].len(), board.len());
+    let gap = |a: usize, b: usize, span: usize| match wrap {
+        true => a.abs_diff(b).min(span - a.abs_diff(b)),
+        false => a.abs_diff(b),
+    };
+    let estimate = |pos: Position| {
+        goals.iter().map(|g| gap(pos.x, g.x, width) + gap(pos.y, g.y, height)).min().unwrap_or(0) as u64
+    };
+    let neighbors = if wrap { wrapping_neighbors } else { neighbors };
+    astar(board, start, goals, costs, neighbors, estimate)
+}
+
+fn astar(
+    board: &[Vec<char>],
+    start: Position,
+    goals: &[Position],
+    costs: &HashMap<Position, u32>,
+    neighbors: fn(&[Vec<char>], Position) -> Vec<Position>,
+    estimate: impl Fn(Position) -> u64,
+) -> Option<Vec<Position>> {
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut spent = HashMap::from([(start, 0u64)]);
+    let mut open = BinaryHeap::from([(Reverse(estimate(start)), start.y, start.x)]);
+
+    while let Some((_, y, x)) = open.pop() {
+        let pos = Position { x, y };
+        if goals.contains(&pos) {
+            let mut path = vec![pos];
+            let mut cur = pos;
+            while cur != start {
+                cur = came_from[&cur];
+                path.push(cur);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let here = spent[&pos];
+        for next in neighbors(board, pos) {
+            let cost = here + u64::from(costs.get(&next).copied().unwrap_or(1));
+            if spent.get(&next).is_none_or(|&known| cost < known) {
+                spent.insert(next, cost);
+                came_from.insert(next, pos);
+                open.push((Reverse(cost + estimate(next)), next.y, next.x));
+            }
+        }
+    }
+    None
+}
+
+/// path_cost
+/// What walking `path` costs, every cell after the first adding its
+/// weight in `costs`, 1 if it has none.
+pub fn path_cost(path: &[Position], costs: &HashMap<Position, u32>) -> u32 {
+    path.iter().skip(1).map(|p| costs.get(p).copied().unwrap_or(1)).sum()
+}
+
+/// Exploration
+/// How far a search has got, for drawing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Exploration {
+    pub expanded: HashSet<Position>,
+    pub frontier: HashSet<Position>,
+}
+
+/// exploration_frames
+/// Up to `max` snapshots of `explore` at work, evenly spaced through its
+/// steps and ending on the last, with the path it found.
+pub fn exploration_frames(
+    board: &[Vec<char>],
+    start: Position,
+    goal: Position,
+    algorithm: SearchAlgorithm,
+    max: usize,
+) -> (Vec<Exploration>, Option<Vec<Position>>) {
+    let mut steps = vec![];
+    let path = explore(board, start, goal, algorithm, |pos, frontier| steps.push((pos, frontier.to_vec())));
+    if steps.is_empty() || max == 0 {
+        return (vec![], path);
+    }
+    let every = steps.len().div_ceil(max);
+    let last = steps.len() - 1;
+    let mut expanded = HashSet::new();
+    let mut frames = vec![];
+    for (i, (pos, frontier)) in steps.into_iter().enumerate() {
+        expanded.insert(pos);
+        if (last - i).is_multiple_of(every) {
+            frames.push(Exploration { expanded: expanded.clone(), frontier: frontier.into_iter().collect() });
+        }
+    }
+    (frames, path)
+}
+
+/// explore
+/// Searches from `start` to `goal` with `algorithm`, calling `visit` each
+/// step with the cell just expanded and the frontier of cells found but
+/// not yet expanded. Returns the path found.
+pub fn explore(
+    board: &[Vec<char>],
+    start: Position,
+    goal: Position,
+    algorithm: SearchAlgorithm,
+    mut visit: impl FnMut(Position, &[Position]),
+) -> Option<Vec<Position>> {
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut steps = HashMap::from([(start, 0)]);
+    let mut expanded = HashSet::new();
+    let mut frontier = vec![start];
+    while !frontier.is_empty() {
+        let next = match algorithm {
+            SearchAlgorithm::BreadthFirst => 0,
+            SearchAlgorithm::DepthFirst => frontier.len() - 1,
+            SearchAlgorithm::AStar => (0..frontier.len())
+                .min_by_key(|&i| steps[&frontier[i]] + manhattan(frontier[i], goal))
+                .unwrap_or(0),
+        };
+        let pos = frontier.remove(next);
+        expanded.insert(pos);
+        visit(pos, &frontier);
+        if pos == goal {
+            let mut path = vec![pos];
+            let mut cur = pos;
+            while cur != start {
+                cur = came_from[&cur];
+                path.push(cur);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let step = steps[&pos] + 1;
+        for next in neighbors(board, pos) {
+            if expanded.contains(&next) || steps.get(&next).is_some_and(|&known| known <= step) {
+                continue;
+            }
+            if steps.insert(next, step).is_none() {
+                frontier.push(next);
+            }
+            came_from.insert(next, pos);
+        }
+    }
+    None
+}
+
+/// distances
+/// Steps from `start` to every open cell it can reach, by flood fill.
+pub fn distances(board: &[Vec<char>], start: Position) -> HashMap<Position, usize> {
+    distances_by(board, start, neighbors)
+}
+
+/// distances_wrapping
+/// `distances` on a board whose edges wrap.
+pub fn distances_wrapping(board: &[Vec<char>], start: Position) -> HashMap<Position, usize> {
+    distances_by(board, start, wrapping_neighbors)
+}
+
+fn distances_by(
+    board: &[Vec<char>],
+    start: Position,
+    neighbors: fn(&[Vec<char>], Position) -> Vec<Position>,
+) -> HashMap<Position, usize> {
+    let mut dist = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        let d = dist[&pos];
+        for next in neighbors(board, pos) {
+            if let Entry::Vacant(e) = dist.entry(next) {
+                e.insert(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+/// is_solvable
+/// Flood fills from `start` and reports whether `goal` was reached.
+pub fn is_solvable(board: &[Vec<char>], start: Position, goal: Position) -> bool {
+    let mut seen = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(pos) = stack.pop() {
+        if pos == goal {
+            return true;
+        }
+        for next in neighbors(board, pos) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+/// neighbors
+/// Orthogonally adjacent cells of `pos` that are in bounds and not a wall.
+pub fn neighbors(board: &[Vec<char>], pos: Position) -> Vec<Position> {
+    let mut moves = vec![];
+    if pos.y > 0 {
+        moves.push(Position { x: pos.x, y: pos.y - 1 });
+    }
+    if pos.x > 0 {
+        moves.push(Position { x: pos.x - 1, y: pos.y });
+    }
+    if pos.y < board.len() - 1 {
+        moves.push(Position { x: pos.x, y: pos.y + 1 });
+    }
+    if pos.x < board[pos.y].len() - 1 {
+        moves.push(Position { x: pos.x + 1, y: pos.y });
+    }
+    moves.retain(|p| board[p.y][p.x] != WALL);
+    moves
+}
+
+/// wrapping_neighbors
+/// Like `neighbors`, but stepping off an edge comes back on the far side.
+pub fn wrapping_neighbors(board: &[Vec<char>], pos: Position) -> Vec<Position> {
+    let (width, height) = (board[0].len(), board.len());
+    let mut moves = vec![
+        Position { x: pos.x, y: (pos.y + height - 1) % height },
+        Position { x: (pos.x + width - 1) % width, y: pos.y },
+        Position { x: pos.x, y: (pos.y + 1) % height },
+        Position { x: (pos.x + 1) % width, y: pos.y },
+    ];
+    moves.retain(|p| *p != pos && board[p.y][p.x] != WALL);
+    moves.dedup();
+    moves
+}
+
+/// path_to_movements
+/// Converts consecutive path positions into the moves that walk them.
+pub fn path_to_movements(path: &[Position]) -> Vec<Movement> {
+    path.windows(2).filter_map(|w| Movement::between(w[0], w[1])).collect()
+}
+
+/// path_to_wrapping_movements
+/// Like `path_to_movements`, for a path over a `width` x `height` board
+/// whose edges wrap around.
+pub fn path_to_wrapping_movements(path: &[Position], (width, height): (usize, usize)) -> Vec<Movement> {
+    path.windows(2).filter_map(|w| Movement::between_wrapping(w[0], w[1], (width, height))).collect()
+}
@@ -0,0 +1,77 @@
+///
+/// Maze Stats Module
+///
+use std::collections::HashMap;
+
+use super::gen::dead_ends;
+use super::solve::{distances, distances_wrapping, solve_nearest, solve_nearest_wrapping, wrapping_neighbors};
+use super::{Position, GOAL, SYMBOL};
+
+/// MazeStats
+/// How hard a board is to solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MazeStats {
+    /// Moves on the shortest route from the player to the nearest goal,
+    /// None if there's no player, no goal or no route.
+    pub path_length: Option<usize>,
+    /// Cells with exactly one open side.
+    pub dead_ends: usize,
+}
+
+/// analyze
+/// Measures a render board, finding the player and goals by their glyphs.
+pub fn analyze(board: &[Vec<char>]) -> MazeStats {
+    let path_length = endpoints(board).and_then(|(start, goals)| solve_nearest(board, start, &goals));
+    MazeStats { path_length: path_length.map(|path| path.len() - 1), dead_ends: dead_ends(board).len() }
+}
+
+/// analyze_wrapping
+/// Like `analyze`, for a board whose edges wrap around.
+pub fn analyze_wrapping(board: &[Vec<char>]) -> MazeStats {
+    let path_length = endpoints(board).and_then(|(start, goals)| solve_nearest_wrapping(board, start, &goals));
+    let dead_ends = (0..board.len()).step_by(2)
+        .flat_map(|y| (0..board[y].len()).step_by(2).map(move |x| Position::new(x, y)))
+        .filter(|&pos| wrapping_neighbors(board, pos).len() == 1)
+        .count();
+    MazeStats { path_length: path_length.map(|path| path.len() - 1), dead_ends }
+}
+
+/// HeatMap
+/// How many steps each reachable cell is from where the player started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatMap {
+    pub distances: HashMap<Position, usize>,
+    /// The furthest distance, the top of the scale.
+    pub max: usize,
+}
+
+impl HeatMap {
+    /// new
+    /// Flood fills `board` from `start`, crossing the edges if they wrap.
+    pub fn new(board: &[Vec<char>], start: Position, wrap: bool) -> HeatMap {
+        let distances = match wrap {
+            true => distances_wrapping(board, start),
+            false => distances(board, start),
+        };
+        let max = distances.values().copied().max().unwrap_or(0);
+        HeatMap { distances, max }
+    }
+
+    /// level
+    /// Where `pos` sits on a scale of `levels` steps, None if unreachable.
+    pub fn level(&self, pos: Position, levels: usize) -> Option<usize> {
+        let d = *self.distances.get(&pos)?;
+        Some((d * levels.saturating_sub(1)).checked_div(self.max).unwrap_or(0))
+    }
+}
+
+/// endpoints
+/// The player and every goal, if there's a player.
+fn endpoints(board: &[Vec<char>]) -> Option<(Position, Vec<Position>)> {
+    let find = |glyph: char| {
+        board.iter().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(move |&(_, &v)| v == glyph).map(move |(x, _)| Position::new(x, y)))
+            .collect::<Vec<_>>()
+    };
+    find(SYMBOL).first().map(|&start| (start, find(GOAL)))
+}
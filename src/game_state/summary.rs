@@ -0,0 +1,80 @@
+///
+/// Run Summary Module
+///
+/// What a game came to, printed when the player quits or wins, whether or
+/// not they reached the goal. As JSON it's one line:
+///
+/// ```text
+/// {"seed":7,"algorithm":"Prim","width":21,"height":21,"moves":96,"time_ms":18250,"optimal":80,"efficiency":1.2,"won":true}
+/// ```
+///
+/// Seed and algorithm are null for loaded mazes, optimal and efficiency
+/// when the goal can't be reached.
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{GameSetup, GameState};
+
+/// RunSummary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub seed: Option<u64>,
+    pub algorithm: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    pub moves: usize,
+    pub time_ms: u64,
+    /// The fewest moves the game could have been won in.
+    pub optimal: Option<usize>,
+    /// Moves made for each optimal one, 1.0 is a perfect run.
+    pub efficiency: Option<f64>,
+    pub won: bool,
+}
+
+impl RunSummary {
+    /// of
+    /// Sums up `state` as it stands, from the `setup` it was built from if
+    /// any and the `optimal` moves measured before play began.
+    pub fn of(state: &GameState, setup: Option<&GameSetup>, optimal: Option<usize>) -> RunSummary {
+        let (width, height) = match setup {
+            Some(setup) => (setup.width, setup.height),
+            None => (state.width(), state.height()),
+        };
+        RunSummary {
+            seed: setup.map(|s| s.seed),
+            algorithm: setup.map(|s| if s.wrap { "Wrapped".to_string() } else { s.algorithm.to_string() }),
+            width,
+            height,
+            moves: state.moves,
+            time_ms: state.elapsed().as_millis() as u64,
+            optimal,
+            efficiency: optimal.filter(|&n| n > 0).map(|n| state.moves as f64 / n as f64),
+            won: state.victory && state.winner.is_none_or(|w| w == 0),
+        }
+    }
+
+    /// to_json
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let or_none = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        writeln!(
+            f,
+            "seed: {}  algorithm: {}  size: {}x{}",
+            or_none(self.seed.map(|s| s.to_string())), or_none(self.algorithm.clone()), self.width, self.height
+        )?;
+        write!(
+            f,
+            "moves: {}  time: {:.1}s  optimal: {}  efficiency: {}  {}",
+            self.moves, self.time_ms as f64 / 1000.0,
+            or_none(self.optimal.map(|n| n.to_string())),
+            or_none(self.efficiency.map(|e| format!("{e:.2}"))),
+            if self.won { "won" } else { "not won" }
+        )
+    }
+}
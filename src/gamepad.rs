@@ -0,0 +1,70 @@
+///
+/// Gamepad Module
+///
+/// Reads gamepads with gilrs, alongside the keyboard. The d-pad and left
+/// stick move, North (Y on an Xbox pad, △ on a PlayStation one) solves
+/// and Start quits.
+use std::sync::mpsc;
+use std::thread;
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use crate::game_state::Movement;
+use crate::input::{stick_direction, PadAction};
+
+/// start
+/// Reads every connected gamepad on a background thread, passing each
+/// action to `send` until it returns false. Fails if gamepads can't be
+/// read at all, having one plugged in later is fine.
+pub fn start(send: impl Fn(PadAction) -> bool + Send + 'static) -> Result<(), String> {
+    let (ready_tx, ready) = mpsc::channel();
+    // Gilrs can't be moved between threads, so it's made on the one
+    // that reads it.
+    thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(e) => {
+                let _ = ready_tx.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let _ = ready_tx.send(Ok(()));
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut leaning = None;
+        while let Some(event) = gilrs.next_event_blocking(None) {
+            let action = match event.event {
+                EventType::ButtonPressed(button, _) => button_action(button),
+                EventType::AxisChanged(axis @ (Axis::LeftStickX | Axis::LeftStickY), value, _) => {
+                    match axis {
+                        Axis::LeftStickX => x = value,
+                        _ => y = value,
+                    }
+                    // The stick moves once each time it's pushed a new
+                    // way, not for as long as it's held.
+                    let direction = stick_direction(x, y);
+                    let pushed = direction.filter(|_| direction != leaning);
+                    leaning = direction;
+                    pushed.map(PadAction::Move)
+                }
+                _ => None,
+            };
+            if action.is_some_and(|action| !send(action)) {
+                break;
+            }
+        }
+    });
+    ready.recv().map_err(|_| "gamepad thread stopped".to_string())?
+}
+
+/// button_action
+fn button_action(button: Button) -> Option<PadAction> {
+    match button {
+        Button::DPadUp => Some(PadAction::Move(Movement::UP)),
+        Button::DPadDown => Some(PadAction::Move(Movement::DOWN)),
+        Button::DPadLeft => Some(PadAction::Move(Movement::LEFT)),
+        Button::DPadRight => Some(PadAction::Move(Movement::RIGHT)),
+        Button::North => Some(PadAction::Solve),
+        Button::Start => Some(PadAction::Quit),
+        _ => None,
+    }
+}
@@ -0,0 +1,264 @@
+///
+/// Hexagonal Maze Module
+///
+/// Hex mazes use "odd-r" offset coordinates: cells are stored in rows, and
+/// every odd row is drawn shifted half a cell to the right. They have their
+/// own six direction movement, so they are played through `HexGame` rather
+/// than `GameState`.
+use std::collections::VecDeque;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// HexDirection
+/// The six neighbors of a hex cell, clockwise from the top right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexDirection {
+    NorthEast,
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::NorthEast,
+        HexDirection::East,
+        HexDirection::SouthEast,
+        HexDirection::SouthWest,
+        HexDirection::West,
+        HexDirection::NorthWest,
+    ];
+
+    /// opposite
+    pub fn opposite(self) -> HexDirection {
+        HexDirection::ALL[(self as usize + 3) % 6]
+    }
+
+    /// from_key
+    /// `w` `e` step up left and right, `a` `d` sideways and `z` `x` down.
+    pub fn from_key(c: char) -> Option<HexDirection> {
+        match c {
+            'w' => Some(HexDirection::NorthWest),
+            'e' => Some(HexDirection::NorthEast),
+            'a' => Some(HexDirection::West),
+            'd' => Some(HexDirection::East),
+            'z' => Some(HexDirection::SouthWest),
+            'x' => Some(HexDirection::SouthEast),
+            _ => None,
+        }
+    }
+}
+
+/// HexBoardCell
+/// A hex cell with a wall on each of its six sides, indexed by direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexBoardCell {
+    pub walls: [bool; 6],
+}
+
+impl HexBoardCell {
+    /// has_wall
+    pub fn has_wall(&self, dir: HexDirection) -> bool {
+        self.walls[dir as usize]
+    }
+}
+
+/// HexMaze
+/// A grid of hex cells indexed `[row][col]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexMaze {
+    pub cells: Vec<Vec<HexBoardCell>>,
+}
+
+impl HexMaze {
+    /// cols
+    pub fn cols(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    /// rows
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// neighbor
+    /// The cell next to `(col, row)` in direction `dir`, if it's in bounds.
+    pub fn neighbor(&self, (col, row): (usize, usize), dir: HexDirection) -> Option<(usize, usize)> {
+        let (col, row) = (col as isize, row as isize);
+        // Odd rows are shifted right, so their diagonal neighbors are too.
+        let shift = row & 1;
+        let (c, r) = match dir {
+            HexDirection::NorthEast => (col + shift, row - 1),
+            HexDirection::East => (col + 1, row),
+            HexDirection::SouthEast => (col + shift, row + 1),
+            HexDirection::SouthWest => (col + shift - 1, row + 1),
+            HexDirection::West => (col - 1, row),
+            HexDirection::NorthWest => (col + shift - 1, row - 1),
+        };
+        let in_bounds = (0..self.cols() as isize).contains(&c) && (0..self.rows() as isize).contains(&r);
+        in_bounds.then_some((c as usize, r as usize))
+    }
+
+    /// open_neighbor
+    /// The neighbor in direction `dir` if there's no wall in the way.
+    pub fn open_neighbor(&self, (col, row): (usize, usize), dir: HexDirection) -> Option<(usize, usize)> {
+        match self.cells[row][col].has_wall(dir) {
+            true => None,
+            false => self.neighbor((col, row), dir),
+        }
+    }
+
+    /// solve
+    /// Shortest path from `start` to `goal` including both ends.
+    pub fn solve(&self, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let mut came_from = vec![vec![None; self.cols()]; self.rows()];
+        came_from[start.1][start.0] = Some(start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            if pos == goal {
+                let mut path = vec![pos];
+                let mut cur = pos;
+                while cur != start {
+                    cur = came_from[cur.1][cur.0].unwrap_or(start);
+                    path.push(cur);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for dir in HexDirection::ALL {
+                if let Some(next) = self.open_neighbor(pos, dir) {
+                    if came_from[next.1][next.0].is_none() {
+                        came_from[next.1][next.0] = Some(pos);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// generate_hex_maze
+/// Recursive backtracker over a `cols` x `rows` hex grid.
+pub fn generate_hex_maze<R: Rng>(cols: usize, rows: usize, rng: &mut R) -> HexMaze {
+    let (cols, rows) = (cols.max(1), rows.max(1));
+    let mut maze = HexMaze { cells: vec![vec![HexBoardCell { walls: [true; 6] }; cols]; rows] };
+    let mut visited = vec![vec![false; cols]; rows];
+    visited[0][0] = true;
+    let mut stack = vec![(0, 0)];
+
+    while let Some(&pos) = stack.last() {
+        let mut moves: Vec<(HexDirection, (usize, usize))> = HexDirection::ALL.into_iter()
+            .filter_map(|dir| maze.neighbor(pos, dir).map(|n| (dir, n)))
+            .filter(|&(_, (c, r))| !visited[r][c])
+            .collect();
+        moves.shuffle(rng);
+        match moves.first() {
+            Some(&(dir, next)) => {
+                maze.cells[pos.1][pos.0].walls[dir as usize] = false;
+                maze.cells[next.1][next.0].walls[dir.opposite() as usize] = false;
+                visited[next.1][next.0] = true;
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    maze
+}
+
+/// render_hex
+/// Draws the maze as character art, one line per string. Each cell is 4
+/// columns wide with `|` side walls and `/` `\` diagonal walls shared with
+/// the rows above and below. The player is `@` and the goal `G`.
+pub fn render_hex(maze: &HexMaze, player: (usize, usize), goal: (usize, usize)) -> Vec<String> {
+    let (width, height) = (4 * maze.cols() + 5, 2 * maze.rows() + 1);
+    let mut canvas = vec![vec![' '; width]; height];
+    for (row, cells) in maze.cells.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let (cx, cy) = (4 * col + 2 * (row & 1) + 2, 2 * row + 1);
+            let walls = [
+                (HexDirection::NorthEast, cx + 1, cy - 1, '/'),
+                (HexDirection::East, cx + 2, cy, '|'),
+                (HexDirection::SouthEast, cx + 1, cy + 1, '\\'),
+                (HexDirection::SouthWest, cx - 1, cy + 1, '/'),
+                (HexDirection::West, cx - 2, cy, '|'),
+                (HexDirection::NorthWest, cx - 1, cy - 1, '\\'),
+            ];
+            for (dir, x, y, glyph) in walls {
+                if cell.has_wall(dir) {
+                    canvas[y][x] = glyph;
+                }
+            }
+            canvas[cy][cx] = match (col, row) {
+                p if p == player => '@',
+                p if p == goal => 'G',
+                _ => ' ',
+            };
+        }
+    }
+    canvas.into_iter().map(|line| line.into_iter().collect::<String>().trim_end().to_string()).collect()
+}
+
+/// HexGame
+/// A hex maze being played, from the top left cell to the bottom right.
+#[derive(Debug, Clone)]
+pub struct HexGame {
+    pub maze: HexMaze,
+    pub position: (usize, usize),
+    pub goal: (usize, usize),
+    pub moves: usize,
+    pub victory: bool,
+}
+
+impl HexGame {
+    /// new
+    pub fn new<R: Rng>(cols: usize, rows: usize, rng: &mut R) -> HexGame {
+        let maze = generate_hex_maze(cols, rows, rng);
+        let goal = (maze.cols() - 1, maze.rows() - 1);
+        HexGame { maze, position: (0, 0), goal, moves: 0, victory: goal == (0, 0) }
+    }
+
+    /// move_position
+    /// Steps in direction `dir` unless a wall is in the way or the game is won.
+    pub fn move_position(&mut self, dir: HexDirection) {
+        if self.victory {
+            return;
+        }
+        if let Some(next) = self.maze.open_neighbor(self.position, dir) {
+            self.position = next;
+            self.moves += 1;
+            self.victory = self.position == self.goal;
+        }
+    }
+
+    /// render
+    pub fn render(&self) -> Vec<String> {
+        render_hex(&self.maze, self.position, self.goal)
+    }
+}
+
+/// Topology
+/// The grid shape a maze is laid out on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    #[default]
+    Square,
+    Hex,
+}
+
+impl std::str::FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Topology, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "square" => Ok(Topology::Square),
+            "hex" => Ok(Topology::Hex),
+            _ => Err(format!("unknown topology {s:?}, expected square or hex")),
+        }
+    }
+}
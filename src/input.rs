@@ -0,0 +1,235 @@
+///
+/// Input Module
+///
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use console::Key;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+use crate::game_state::Movement;
+
+/// KeyBindings
+/// Maps keyboard characters to game actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default, deny_unknown_fields, rename_all = "kebab-case"))]
+pub struct KeyBindings {
+    pub up: char,
+    pub left: char,
+    pub down: char,
+    pub right: char,
+    pub up_left: char,
+    pub up_right: char,
+    pub down_left: char,
+    pub down_right: char,
+    pub solve: char,
+    pub hint: char,
+    pub trail: char,
+    pub heat_map: char,
+    pub undo: char,
+    pub save: char,
+    pub copy: char,
+    pub restart: char,
+    pub new_maze: char,
+    pub zoom: char,
+    pub pause: char,
+    pub quit: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            up: 'w',
+            left: 'a',
+            down: 's',
+            right: 'd',
+            // Laid out like the number pad.
+            up_left: '7',
+            up_right: '9',
+            down_left: '1',
+            down_right: '3',
+            solve: 'p',
+            hint: 'h',
+            trail: 't',
+            heat_map: 'm',
+            undo: 'u',
+            save: 'o',
+            copy: 'c',
+            restart: 'r',
+            new_maze: 'n',
+            zoom: 'z',
+            pause: ' ',
+            quit: 'q',
+        }
+    }
+}
+
+impl KeyBindings {
+    /// from_layout
+    /// Accepts the movement keys in WASD order, e.g. "ijkl". Returns None
+    /// unless exactly four keys are given, none of them already bound to
+    /// something else.
+    pub fn from_layout(layout: &str) -> Option<KeyBindings> {
+        KeyBindings::default().with_layout(layout)
+    }
+
+    /// with_layout
+    /// Like `from_layout`, keeping the rest of these bindings.
+    pub fn with_layout(self, layout: &str) -> Option<KeyBindings> {
+        let keys: Vec<char> = layout.chars().collect();
+        if let [up, left, down, right] = keys[..] {
+            Some(KeyBindings { up, left, down, right, ..self }).filter(|keys| keys.clash().is_none())
+        } else {
+            None
+        }
+    }
+
+    /// clash
+    /// The first key bound to more than one action, if any. Actions are
+    /// checked before movement, so a clashing move key would never move.
+    pub fn clash(&self) -> Option<char> {
+        let keys = [
+            self.up, self.left, self.down, self.right, self.up_left, self.up_right, self.down_left, self.down_right,
+            self.solve, self.hint, self.trail, self.heat_map, self.undo, self.save, self.copy, self.restart,
+            self.new_maze, self.zoom, self.pause, self.quit,
+        ];
+        keys.iter().enumerate().find(|&(i, key)| keys[..i].contains(key)).map(|(_, &key)| key)
+    }
+
+    /// legend
+    /// A one line reminder of the main controls, as currently bound.
+    pub fn legend(&self) -> String {
+        let moves: String = [self.up, self.left, self.down, self.right].iter().flat_map(|c| c.to_uppercase()).collect();
+        let name = |c: char| match c {
+            ' ' => "space".to_string(),
+            c => c.to_string(),
+        };
+        format!(
+            "{moves}: move  {}: undo  {}: hint  {}: solve  {}: pause  {}: restart  {}: quit",
+            name(self.undo), name(self.hint), name(self.solve), name(self.pause), name(self.restart), name(self.quit)
+        )
+    }
+
+    /// movement
+    /// Returns the movement bound to `c`, if any.
+    pub fn movement(&self, c: char) -> Option<Movement> {
+        match c {
+            c if c == self.up => Some(Movement::UP),
+            c if c == self.left => Some(Movement::LEFT),
+            c if c == self.down => Some(Movement::DOWN),
+            c if c == self.right => Some(Movement::RIGHT),
+            c if c == self.up_left => Some(Movement::UP_LEFT),
+            c if c == self.up_right => Some(Movement::UP_RIGHT),
+            c if c == self.down_left => Some(Movement::DOWN_LEFT),
+            c if c == self.down_right => Some(Movement::DOWN_RIGHT),
+            _ => None,
+        }
+    }
+}
+
+/// is_f1
+/// True for the escape sequences terminals send for F1: `ESC O P` from
+/// xterm-likes, `ESC [ 1 1 ~` from rxvt and `ESC [ [ A` from the Linux
+/// console. `read_key` only reads the first few characters of them, the
+/// rest arrive as separate unbound keys.
+pub fn is_f1(key: &Key) -> bool {
+    match key {
+        Key::UnknownEscSeq(seq) => matches!(seq[..], ['O'] | ['[', '1', '1'] | ['[', '[', 'A']),
+        _ => false,
+    }
+}
+
+/// PadAction
+/// What a gamepad's buttons and left stick are for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadAction {
+    Move(Movement),
+    Solve,
+    Quit,
+}
+
+/// How far the stick must lean before it counts as pointing somewhere.
+const STICK_DEADZONE: f32 = 0.5;
+
+/// stick_direction
+/// The way a stick at `x`, `y` (each -1.0 to 1.0, up and right positive)
+/// points, by whichever axis it leans furthest along. None while it's
+/// near the middle.
+pub fn stick_direction(x: f32, y: f32) -> Option<Movement> {
+    if x.abs().max(y.abs()) < STICK_DEADZONE {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0.0 { Movement::RIGHT } else { Movement::LEFT })
+    } else {
+        Some(if y > 0.0 { Movement::UP } else { Movement::DOWN })
+    }
+}
+
+/// Presses of the same key closer together than this are the terminal's
+/// auto-repeat, not separate taps. Gone quiet this long, a repeating key
+/// counts as released.
+const RELEASE_GAP: Duration = Duration::from_millis(100);
+/// How often the repeater thread looks for released keys and due repeats.
+const REPEAT_POLL: Duration = Duration::from_millis(10);
+
+/// Repeater
+/// Turns a held key into actions at a steady rate. Terminals only report
+/// presses, so a key counts as held once its auto-repeat starts arriving
+/// and released when that stops. Every other press is acted on straight
+/// away, while the terminal's own repeats are swallowed and replaced by
+/// one action every `interval` from a background thread.
+pub struct Repeater<A> {
+    held: Arc<Mutex<Option<Held<A>>>>,
+    interval: Duration,
+}
+
+struct Held<A> {
+    action: A,
+    last_seen: Instant,
+    /// When the next repeat is due, None until auto-repeat shows the key
+    /// is being held.
+    next: Option<Instant>,
+}
+
+impl<A: Copy + PartialEq + Send + 'static> Repeater<A> {
+    /// start
+    /// Repeats held actions through `send` until it returns false.
+    pub fn start(interval: Duration, send: impl Fn(A) -> bool + Send + 'static) -> Repeater<A> {
+        let held: Arc<Mutex<Option<Held<A>>>> = Arc::new(Mutex::new(None));
+        let shared = Arc::clone(&held);
+        thread::spawn(move || loop {
+            thread::sleep(interval.min(REPEAT_POLL));
+            let mut held = shared.lock().unwrap_or_else(|e| e.into_inner());
+            if held.as_ref().is_some_and(|h| h.last_seen.elapsed() >= RELEASE_GAP) {
+                *held = None;
+            }
+            let now = Instant::now();
+            let action = held.as_mut().filter(|h| h.next.is_some_and(|next| now >= next)).map(|h| {
+                h.next = Some(now + interval);
+                h.action
+            });
+            drop(held);
+            if action.is_some_and(|action| !send(action)) {
+                break;
+            }
+        });
+        Repeater { held, interval }
+    }
+
+    /// press
+    /// Notes a press of the key for `action`, returning whether it should
+    /// be acted on now rather than left to the repeater.
+    pub fn press(&self, action: A) -> bool {
+        let mut held = self.held.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if let Some(h) = held.as_mut().filter(|h| h.action == action && now - h.last_seen < RELEASE_GAP) {
+            h.last_seen = now;
+            h.next.get_or_insert(now + self.interval);
+            return false;
+        }
+        *held = Some(Held { action, last_seen: now, next: None });
+        true
+    }
+}
@@ -0,0 +1,26 @@
+///
+/// MazeMe Library
+///
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod clipboard;
+pub mod daily;
+pub mod editor;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod game_state;
+pub mod hex;
+pub mod input;
+pub mod menu;
+pub mod render;
+
+pub use game_state::gen::{
+    braid, generate_maze, generate_maze_eller, generate_maze_kruskal, generate_maze_prim,
+    generate_maze_recursive_division, generate_maze_seeded, generate_maze_wilson, generate_maze_with,
+    generate_maze_with_rng, render_dims, Difficulty, MazeAlgorithm,
+};
+pub use game_state::solve::{euclidean, is_solvable, manhattan, solve, solve_astar, solve_dfs};
+pub use game_state::stats::{analyze, MazeStats};
+pub use game_state::{BoardCell, GameSetup, GameState, GameStateHandler, Movement, Player, Position, Walls};
+pub use hex::{generate_hex_maze, render_hex, HexBoardCell, HexDirection, HexGame, HexMaze, Topology};
+pub use input::KeyBindings;
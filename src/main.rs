@@ -1,68 +1,1395 @@
-use console::Term;
-use std::io::Read;
-use std::sync::mpsc::{self, SendError};
-use std::{thread, io};
-use std::time::Duration;
-mod game_state;
-mod render;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use console::{Key, Term};
+use figlet_rs::FIGfont;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, LineWriter, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use game_state::Movement::*;
-use game_state::Clock::*;
-use game_state::{GameState, GameStateHandler};
-use render::start_render;
-
-use self::game_state::StateEvent;
+use mazeme::config::Config;
+use mazeme::daily::Date;
+use mazeme::editor::{Editor, EditorAction};
+use mazeme::game_state::Movement::*;
+use mazeme::game_state::net::{Peer, DEFAULT_PORT};
+use mazeme::game_state::replay::{Recorder, Replay, ReplayHeader};
+use mazeme::game_state::scores::{Leaderboard, Score};
+use mazeme::game_state::solve::{exploration_frames, SearchAlgorithm};
+use mazeme::game_state::summary::RunSummary;
+use mazeme::game_state::{FinishHook, Hooks, StateEvent, MAX_FOG_CLOSING};
+use mazeme::hex::{HexDirection, HexGame, Topology};
+use mazeme::input::{is_f1, PadAction, Repeater};
+use mazeme::menu::{Menu, MenuAction};
+use mazeme::{
+    render_dims, Difficulty, GameSetup, GameState, GameStateHandler, KeyBindings, MazeAlgorithm, Movement, Position,
+};
+use mazeme::render::stream::Record;
+use mazeme::render::{
+    export_ascii, export_png, import_ascii, start_render, strip_colors, Glyphs, RenderConfig, Renderer, TerminalGuard,
+    Theme, WallStyle,
+};
 
 /// main function
 fn main() {
-    let board_size = determine_board_size();
+    handle_interrupts();
+    let config = load_config();
+    let mut options = Options::parse_valid(&config);
+    // The daily maze can't depend on the terminal, so it's always the
+    // default difficulty's size.
+    let daily = options.daily.then(Date::today);
+    if let Some(date) = daily {
+        let preset = Difficulty::default().size();
+        options.seed = Some(date.seed());
+        options.size = Some((preset, preset));
+    }
+    // Pipes, log files and NO_COLOR (https://no-color.org) all get plain output.
+    if !Term::stdout().is_term() || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        options.no_color = true;
+    }
+    if let Some(count) = options.bench {
+        if let Err(e) = run_bench(&options, count) {
+            Options::command().error(ErrorKind::ValueValidation, e).exit();
+        }
+        return;
+    }
+    if options.scores {
+        print_scores();
+        return;
+    }
+    if options.spectate {
+        spectate();
+        return;
+    }
+    if options.topology == Some(Topology::Hex) {
+        run_hex(&options);
+        return;
+    }
+    // Gamepads are looked for up front, so a failure is reported before
+    // the terminal is taken over.
+    let pad = options.gamepad.then(start_gamepad);
+    let show_menu = Term::stdout().is_term()
+        && options.size.is_none()
+        && options.seed.is_none()
+        && options.algo.is_none()
+        && options.difficulty.is_none()
+        && options.braid.is_none()
+        && !options.wrap
+        && options.topology.is_none()
+        && options.load.is_none()
+        && options.load_ascii.is_none()
+        && options.edit.is_none()
+        && options.export.is_none()
+        && options.png.is_none()
+        && options.replay.is_none()
+        && options.host.is_none()
+        && options.join.is_none();
+    let editor = options.edit.as_deref().map(|path| open_editor(path, options.size));
+    let mut terminal = (show_menu || editor.is_some()).then(TerminalGuard::setup);
+    if show_menu && !run_menu(&mut options) {
+        return;
+    }
+    if let Some(editor) = editor {
+        if !run_editor(editor, &mut options) {
+            return;
+        }
+    }
+
+    let replay = options.replay.as_deref().map(load_replay);
+    let joined = options.join.as_deref().map(join_game);
+    let mut setup = None;
+    let mut state = match (&options.load, &options.load_ascii, &replay) {
+        (Some(path), _, _) => load_game(path, options.size),
+        (None, Some(path), _) => load_ascii(path),
+        (None, None, Some(replay)) => replay.header.setup.try_build().unwrap_or_else(|e| {
+            drop(terminal.take());
+            println!("could not replay: {e}");
+            std::process::exit(1);
+        }),
+        (None, None, None) => {
+            let game = match &joined {
+                Some(peer) => peer.setup.try_build().map(|state| (state, peer.setup)),
+                None => new_game(&options, options.seed.unwrap_or_else(rand::random)),
+            };
+            let (state, game_setup) = game.unwrap_or_else(|e| {
+                drop(terminal.take());
+                Options::command().error(ErrorKind::ValueValidation, e).exit()
+            });
+            setup = Some(game_setup);
+            state
+        }
+    };
+    let peer = match (joined, &options.host) {
+        (Some(peer), _) => Some(peer),
+        (None, Some(addr)) => setup.map(|setup| host_game(addr, setup)),
+        (None, None) => None,
+    };
+    ready(&mut state, &options, replay.as_ref(), peer.is_some(), setup.is_some());
+    if let Some(path) = &options.export {
+        if let Err(e) = fs::write(path, export_ascii(state.board())) {
+            println!("could not export to {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = &options.png {
+        if let Err(e) = export_png(state.board(), path, options.cell_px) {
+            println!("could not export to {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+    if options.export.is_some() || options.png.is_some() {
+        return;
+    }
+
+    let keys = load_key_bindings(config.keys.unwrap_or_default());
+    let recording = setup.zip(options.record.as_deref()).map(|(setup, path)| {
+        let countdown = state.countdown().unwrap_or(0);
+        let header = ReplayHeader {
+            setup, enemy_seed: rand::random(), fog: options.fog, fog_closing: options.fog_closing, par: state.par,
+            countdown,
+        };
+        let recorder = Recorder::create(path, &header).unwrap_or_else(|e| {
+            drop(terminal.take());
+            println!("could not record to {}: {e}", path.display());
+            std::process::exit(1);
+        });
+        (header, recorder)
+    });
+    // Opening a FIFO waits here for the spectator to start reading.
+    let stream_out = options.stream.as_deref().map(|path| {
+        fs::File::create(path).unwrap_or_else(|e| {
+            drop(terminal.take());
+            println!("could not stream to {}: {e}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    let terminal = terminal.unwrap_or_else(TerminalGuard::setup);
+    // Only generated mazes can be swapped for another, and not the daily
+    // one, a race over the network or a recording, which hold just the one.
+    let renewable = setup.is_some() && peer.is_none() && recording.is_none() && daily.is_none();
+    let mut session = Session {
+        options: &options,
+        keys: &keys,
+        replay: replay.as_ref(),
+        daily,
+        stream_out: stream_out.as_ref(),
+        inputs: pad.map(|(tx, rx)| Inputs::start(tx, rx)),
+        renewable,
+    };
+    let mut game = Game { state, setup, peer, recording };
+    let mut summaries = Vec::new();
+    let renderer = loop {
+        let played = play(game, &mut session);
+        summaries.push(played.summary);
+        if !played.new_maze {
+            break played.renderer;
+        }
+        if let Some(e) = played.renderer.finish() {
+            drop(terminal);
+            eprintln!("could not draw the game: {e}");
+            std::process::exit(1);
+        }
+        let (mut state, setup) = match new_game(&options, rand::random()) {
+            Ok(game) => game,
+            Err(e) => {
+                drop(terminal);
+                println!("could not make a new maze: {e}");
+                std::process::exit(1);
+            }
+        };
+        ready(&mut state, &options, None, false, true);
+        game = Game { state, setup: Some(setup), peer: None, recording: None };
+    };
+    drop(terminal);
+    if let Some(e) = renderer.crash() {
+        eprintln!("could not draw the game: {e}");
+        std::process::exit(1);
+    }
+    for summary in summaries {
+        let summary = summary.lock().unwrap_or_else(|e| e.into_inner());
+        match options.stats_json {
+            true => eprintln!("{}", summary.to_json()),
+            false => eprintln!("{summary}"),
+        }
+    }
+}
+
+/// ready
+/// Sets `state` up for play from the options, or how `replay` was played.
+/// Racing with `--race`, or over the network if `networked`, adds the
+/// second player, and only `generated` mazes can have a par.
+fn ready(state: &mut GameState, options: &Options, replay: Option<&Replay>, networked: bool, generated: bool) {
+    state.visibility_radius = replay.map_or(options.fog, |r| r.header.fog);
+    state.fog_closing = replay.map_or(options.fog_closing, |r| r.header.fog_closing);
+    let racing = options.race || networked;
+    if racing {
+        state.add_rival();
+    }
+    match (replay, options.par) {
+        (Some(replay), _) => state.par = replay.header.par,
+        (None, Some(factor)) if generated => state.set_par(factor),
+        _ => (),
+    }
+    state.start_countdown(match replay {
+        Some(replay) => replay.header.countdown,
+        None => options.countdown.unwrap_or(if racing { 3 } else { 0 }),
+    });
+}
+
+/// Game
+/// A maze ready to play, and what it was made from.
+struct Game {
+    state: GameState,
+    setup: Option<GameSetup>,
+    peer: Option<Peer>,
+    recording: Option<(ReplayHeader, Recorder)>,
+}
+
+/// Session
+/// What stays the same from one game to the next, see `play`.
+struct Session<'a> {
+    options: &'a Options,
+    keys: &'a KeyBindings,
+    replay: Option<&'a Replay>,
+    daily: Option<Date>,
+    /// Where frames are streamed for `--spectate`, if anywhere.
+    stream_out: Option<&'a fs::File>,
+    /// Keys and gamepad actions, None to read keys straight from the
+    /// terminal.
+    inputs: Option<Inputs>,
+    /// The new maze key swaps the game for another.
+    renewable: bool,
+}
+
+/// Played
+/// How a game ended.
+struct Played {
+    renderer: Renderer,
+    summary: Arc<Mutex<RunSummary>>,
+    /// The player asked for another maze, rather than quitting.
+    new_maze: bool,
+}
+
+/// play
+/// Plays `game` until the player quits or asks for a new maze.
+fn play(game: Game, session: &mut Session) -> Played {
+    let Session { options, keys, replay, daily, .. } = *session;
+    let Game { mut state, setup, peer, recording } = game;
+    let save_path = options.load.clone().unwrap_or_else(|| PathBuf::from(SAVE_FILE));
+
+    let racing = state.rival.is_some();
+    // Races are between the players, not against the leaderboard.
+    let finish = setup.filter(|_| !racing).and_then(score_hook);
+    // In a race the arrow keys move the second player.
+    let arrow_player = usize::from(options.race);
+    let run_setup = setup.or(replay.as_ref().map(|r| r.header.setup));
+    let optimal = state.fewest_moves();
 
     let (tx, rx) = mpsc::channel();
-    let state = GameState::new(board_size);
-    start_render(rx, state.board.len());
-    let state_handler = GameStateHandler::new(state, tx);
+    let renderer = start_render(rx, RenderConfig {
+        theme: options.theme,
+        color: !options.no_color,
+        sound: !options.quiet,
+        win_text: options.win_text.clone(),
+        font: options.font.clone(),
+        // Replays take no input besides quitting.
+        legend: (!options.no_help && replay.is_none()).then(|| keys.legend()),
+        header: run_setup.map(|setup| match daily {
+            Some(date) => format!("Daily Challenge {date}  {}", setup.title()),
+            None => setup.title(),
+        }),
+        glyphs: Glyphs { player: options.player_glyph.clone(), goal: options.goal_glyph.clone() },
+        walls: options.walls,
+        compact: options.compact,
+        half_blocks: options.half_blocks,
+        zoom: 0,
+    });
+    if let Some(setup) = setup.filter(|_| options.animate_gen) {
+        animate_generation(&tx, &setup);
+        state.started = Instant::now();
+    }
+    if let Some(algorithm) = options.visualize_solve {
+        visualize_solve(&tx, &state, algorithm);
+        state.started = Instant::now();
+    }
+    // Over the network every state passes through the peer on its way to
+    // be drawn.
+    let (tx, net) = match peer {
+        Some(peer) => {
+            let (net_tx, net_rx) = mpsc::channel();
+            (net_tx, Some((peer, net_rx, tx)))
+        }
+        None => (tx, None),
+    };
+    // However the game ends, its last state is summed up on exit.
+    let summary = Arc::new(Mutex::new(RunSummary::of(&state, run_setup.as_ref(), optimal)));
+    let tx = match session.stream_out.and_then(|out| out.try_clone().ok()) {
+        Some(out) => stream(tx, out),
+        None => tx,
+    };
+    let tx = summarize(tx, Arc::clone(&summary), run_setup, optimal);
+    let diagonals = state.diagonals;
+    let state_handler = match (replay, recording) {
+        (Some(replay), _) => GameStateHandler::replayed(state, tx, replay.header.enemy_seed, replay.state_events()),
+        (None, Some((header, mut recorder))) => {
+            let hook = Box::new(move |ev: &StateEvent| {
+                let _ = recorder.record(ev);
+            });
+            GameStateHandler::hooked(state, tx, header.enemy_seed, Hooks { event: Some(hook), finish })
+        }
+        (None, None) => GameStateHandler::hooked(state, tx, rand::random(), Hooks { event: None, finish }),
+    };
     let move_channel = state_handler.get_sender();
-    let time_channel = state_handler.get_sender();
+    if let Some((peer, states, render)) = net {
+        peer.start(states, render, move_channel.clone());
+    }
+
+    let repeater = (options.repeat_ms > 0).then(|| {
+        let sender = move_channel.clone();
+        let interval = Duration::from_millis(options.repeat_ms);
+        Repeater::start(interval, move |action| sender.send(player_move(action)).is_ok())
+    });
+    let press = |action| match repeater.as_ref().is_none_or(|r| r.press(action)) {
+        true => move_channel.send(player_move(action)),
+        false => Ok(()),
+    };
+
+    let stdout = Term::buffered_stdout();
+    let mut new_maze = false;
+    PLAYING.store(true, Ordering::SeqCst);
+    // Once the renderer has stopped the next key press ends the game,
+    // rather than playing on blind.
+    while renderer.is_running() && !INTERRUPTED.load(Ordering::SeqCst) {
+        let input = match session.inputs.as_mut() {
+            Some(inputs) => inputs.next(),
+            None => read_input(&stdout),
+        };
+        let Some(input) = input else {
+            // Without the key and gamepad threads there's nothing more to read.
+            match session.inputs {
+                Some(_) => break,
+                None => continue,
+            }
+        };
+        if match input {
+            Input::Key(Key::Escape) | Input::Pad(PadAction::Quit) | Input::Interrupt => break,
+            Input::Key(Key::Char(c)) if c == keys.quit => break,
+            // Replays take no input besides quitting.
+            _ if replay.is_some() => continue,
+            Input::Pad(PadAction::Solve) => move_channel.send(StateEvent::Solve),
+            // The stick and d-pad move once a push, leaving nothing for
+            // the repeater to do.
+            Input::Pad(PadAction::Move(mv)) => move_channel.send(player_move((0, mv))),
+            Input::Key(Key::Char(c)) if c == keys.solve => move_channel.send(StateEvent::Solve),
+            Input::Key(Key::Char(c)) if c == keys.hint => move_channel.send(StateEvent::Hint),
+            Input::Key(Key::Char(c)) if c == keys.trail => move_channel.send(StateEvent::ToggleTrail),
+            Input::Key(Key::Char(c)) if c == keys.heat_map => move_channel.send(StateEvent::ToggleHeatMap),
+            Input::Key(ref key) if is_f1(key) => move_channel.send(StateEvent::ToggleSolution),
+            Input::Key(Key::Char(c)) if c == keys.undo => move_channel.send(StateEvent::Undo),
+            Input::Key(Key::Char(c)) if c == keys.pause => move_channel.send(StateEvent::Pause),
+            Input::Key(Key::Char(c)) if c == keys.save => move_channel.send(StateEvent::Save(save_path.clone())),
+            Input::Key(Key::Char(c)) if c == keys.copy => move_channel.send(StateEvent::Copy),
+            Input::Key(Key::Char(c)) if c == keys.restart => move_channel.send(StateEvent::Restart),
+            Input::Key(Key::Char(c)) if c == keys.zoom => move_channel.send(StateEvent::Zoom),
+            Input::Key(Key::Char(c)) if c == keys.new_maze && session.renewable => {
+                new_maze = true;
+                // The game, and every thread drawing it, stops before the
+                // next one starts.
+                let _ = move_channel.send(StateEvent::Quit);
+                break;
+            }
+            Input::Key(Key::Char(c)) => match keys.movement(c).filter(|mv| diagonals || mv.parts().is_none()) {
+                Some(mv) => press((0, mv)),
+                None => move_channel.send(StateEvent::NoOP),
+            },
+            Input::Key(Key::ArrowUp) => press((arrow_player, UP)),
+            Input::Key(Key::ArrowLeft) => press((arrow_player, LEFT)),
+            Input::Key(Key::ArrowDown) => press((arrow_player, DOWN)),
+            Input::Key(Key::ArrowRight) => press((arrow_player, RIGHT)),
+            _ => move_channel.send(StateEvent::NoOP),
+        }.is_err() {
+            break;
+        }
+    }
+    Played { renderer, summary, new_maze }
+}
+
+/// Input
+/// A key press, or what a gamepad asked for.
+enum Input {
+    Key(Key),
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    Pad(PadAction),
+    /// Ctrl-C.
+    Interrupt,
+}
+
+/// read_input
+/// The next key pressed on `term`, None if it couldn't be read.
+fn read_input(term: &Term) -> Option<Input> {
+    match term.read_key() {
+        Ok(key) => Some(Input::Key(key)),
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => Some(Input::Interrupt),
+        Err(_) => None,
+    }
+}
+
+/// handle_interrupts
+/// Makes Ctrl-C during a game quit it like the quit key, see `read_input`.
+/// Anywhere else the terminal is put back before exiting.
+fn handle_interrupts() {
+    let handled = ctrlc::set_handler(|| {
+        if PLAYING.load(Ordering::SeqCst) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            return;
+        }
+        let _stdout = TerminalGuard::restore();
+        std::process::exit(130);
+    });
+    if let Err(e) = handled {
+        eprintln!("warning: Ctrl-C may leave the terminal in a bad state: {e}");
+    }
+}
 
+/// Inputs
+/// Keys and gamepad actions read on threads of their own, so either can
+/// end the game while the other waits. A key is only read once asked for,
+/// so the game doesn't end with the next one half read.
+struct Inputs {
+    rx: mpsc::Receiver<Input>,
+    want_key: mpsc::Sender<()>,
+    asked: bool,
+}
+
+impl Inputs {
+    /// start
+    /// Reads keys into `tx` alongside the gamepad already sending on it.
+    fn start(tx: mpsc::Sender<Input>, rx: mpsc::Receiver<Input>) -> Inputs {
+        let (want_key, wanted) = mpsc::channel();
+        thread::spawn(move || {
+            let term = Term::stdout();
+            for () in wanted {
+                let input = loop {
+                    if let Some(input) = read_input(&term) {
+                        break input;
+                    }
+                };
+                if tx.send(input).is_err() {
+                    break;
+                }
+            }
+        });
+        Inputs { rx, want_key, asked: false }
+    }
+
+    /// next
+    /// Waits for a key or gamepad action, None once neither is left to
+    /// send one.
+    fn next(&mut self) -> Option<Input> {
+        if !self.asked {
+            self.asked = self.want_key.send(()).is_ok();
+        }
+        let input = self.rx.recv().ok()?;
+        self.asked &= matches!(input, Input::Pad(_));
+        Some(input)
+    }
+}
 
+/// start_gamepad
+/// Reads gamepads into a channel that keys can join later, see
+/// `Inputs::start`. Exits if gamepads can't be read.
+#[cfg(feature = "gamepad")]
+fn start_gamepad() -> (mpsc::Sender<Input>, mpsc::Receiver<Input>) {
+    let (tx, rx) = mpsc::channel();
+    let pad = tx.clone();
+    if let Err(e) = mazeme::gamepad::start(move |action| pad.send(Input::Pad(action)).is_ok()) {
+        println!("could not read gamepads: {e}");
+        std::process::exit(1);
+    }
+    (tx, rx)
+}
+
+/// start_gamepad
+#[cfg(not(feature = "gamepad"))]
+fn start_gamepad() -> (mpsc::Sender<Input>, mpsc::Receiver<Input>) {
+    println!("--gamepad needs mazeme built with `--features gamepad`");
+    std::process::exit(1);
+}
+
+/// summarize
+/// Passes states on to `tx`, keeping `summary` up to date with the latest.
+fn summarize(
+    tx: mpsc::Sender<GameState>,
+    summary: Arc<Mutex<RunSummary>>,
+    setup: Option<GameSetup>,
+    optimal: Option<usize>,
+) -> mpsc::Sender<GameState> {
+    let (summary_tx, states) = mpsc::channel::<GameState>();
+    thread::spawn(move || {
+        for state in states {
+            *summary.lock().unwrap_or_else(|e| e.into_inner()) = RunSummary::of(&state, setup.as_ref(), optimal);
+            if tx.send(state).is_err() {
+                break;
+            }
+        }
+    });
+    summary_tx
+}
+
+/// stream
+/// Passes states on to `tx`, writing each to `out` as a line for
+/// spectators, see `Record`. The game goes on if the spectator leaves.
+fn stream(tx: mpsc::Sender<GameState>, out: fs::File) -> mpsc::Sender<GameState> {
+    let (stream_tx, states) = mpsc::channel::<GameState>();
     thread::spawn(move || {
-        loop {
-            thread::sleep(Duration::from_millis(1000));
-            if let Err(_) = time_channel.send(StateEvent::Clock(SUB(1))) {
+        let mut out = Some(LineWriter::new(out));
+        for state in states {
+            if out.as_mut().is_some_and(|out| writeln!(out, "{}", Record::of(&state).to_line()).is_err()) {
+                out = None;
+            }
+            if tx.send(state).is_err() {
                 break;
             }
         }
     });
+    stream_tx
+}
+
+/// spectate
+/// Shows the frames of a game streamed on stdin with `--stream` as they
+/// arrive, leaving the last one on screen once the stream ends.
+fn spectate() {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        println!("--spectate reads a game streamed with --stream on stdin, e.g. `mazeme --spectate < FIFO`");
+        std::process::exit(1);
+    }
+    let terminal = TerminalGuard::setup();
+    let mut last = None;
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        // Anything else in the stream is skipped.
+        let Ok(record) = Record::parse(&line) else {
+            continue;
+        };
+        print!("\x1b[2J\x1b[H{}{}", record.frame, record.status);
+        let _ = io::stdout().flush();
+        last = Some(record);
+    }
+    drop(terminal);
+    if let Some(record) = last {
+        println!("{}{}", record.frame, record.status);
+    }
+}
+
+/// player_move
+/// The event moving `player` of a race, or the only player if it's 0.
+fn player_move((player, mv): (usize, Movement)) -> StateEvent {
+    match player {
+        0 => StateEvent::Movement(mv),
+        _ => StateEvent::PlayerMovement(player, mv),
+    }
+}
+
+/// host_game
+/// Waits for a player to join on `addr` and shares `setup` with them.
+fn host_game(addr: &str, setup: GameSetup) -> Peer {
+    let peer = TcpListener::bind(with_port(addr)).and_then(|listener| {
+        println!("Waiting for a player to join on {}...", listener.local_addr()?);
+        Peer::host(&listener, setup)
+    });
+    peer.unwrap_or_else(|e| {
+        println!("could not host on {addr}: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// join_game
+/// Connects to the game hosted at `addr`.
+fn join_game(addr: &str) -> Peer {
+    Peer::join(with_port(addr)).unwrap_or_else(|e| {
+        println!("could not join {addr}: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// with_port
+/// `addr`, with DEFAULT_PORT if it doesn't name one.
+fn with_port(addr: &str) -> String {
+    match addr.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => addr.to_string(),
+        _ => format!("{addr}:{DEFAULT_PORT}"),
+    }
+}
+
+/// animate_generation
+/// Sends the maze being generated to the renderer a step at a time, before
+/// the game starts.
+fn animate_generation(tx: &mpsc::Sender<GameState>, setup: &GameSetup) {
+    for board in setup.generation_frames(GENERATION_FRAMES) {
+        let (width, height) = (board[0].len(), board.len());
+        let frame = GameState::from_board(board, Position::new(0, 0), Position::new(width - 1, height - 1));
+        if tx.send(frame).is_err() {
+            return;
+        }
+        thread::sleep(GENERATION_FRAME_DELAY);
+    }
+}
+
+/// visualize_solve
+/// Shows `algorithm` searching from the player to the goal a step at a
+/// time, then the path it found, before the game starts.
+fn visualize_solve(tx: &mpsc::Sender<GameState>, state: &GameState, algorithm: SearchAlgorithm) {
+    let (board, start, goal) = (state.board(), state.position, state.win_position);
+    let (frames, path) = exploration_frames(board, start, goal, algorithm, SEARCH_FRAMES);
+    let expanded = frames.last().map_or(0, |e| e.expanded.len());
+    for exploration in frames {
+        let mut frame = state.clone();
+        frame.exploration = Some(exploration);
+        frame.message = Some(format!("Searching with {algorithm}"));
+        if tx.send(frame).is_err() {
+            return;
+        }
+        thread::sleep(SEARCH_FRAME_DELAY);
+    }
+    let mut frame = state.clone();
+    frame.message = Some(match &path {
+        Some(path) => format!("{algorithm} found {} steps after expanding {expanded} cells", path.len() - 1),
+        None => format!("{algorithm} found no path after expanding {expanded} cells"),
+    });
+    frame.solution_path = path.map(|path| path.into_iter().collect());
+    if tx.send(frame).is_ok() {
+        thread::sleep(SEARCH_RESULT_DELAY);
+    }
+}
+
+/// score_hook
+/// Records the result of a won game on the leaderboard, reporting a new
+/// best or the one to beat. None if there's nowhere to keep scores.
+fn score_hook(setup: GameSetup) -> Option<FinishHook> {
+    let path = Leaderboard::default_path()?;
+    Some(Box::new(move |state: &GameState| {
+        let mut board = match Leaderboard::load(&path) {
+            Ok(board) => board,
+            Err(e) => return Some(format!("Scores not saved: {e}")),
+        };
+        let previous = board.best(&setup);
+        if !board.submit(setup, Score::of(state)) {
+            return previous.map(|best| format!("Best: {:.1?} in {} moves", best.time(), best.moves));
+        }
+        Some(match board.save(&path) {
+            Ok(()) => "New best time!".to_string(),
+            Err(e) => format!("Scores not saved: {e}"),
+        })
+    }))
+}
+
+/// print_scores
+/// Prints the leaderboard, smallest boards first.
+fn print_scores() {
+    let Some(path) = Leaderboard::default_path() else {
+        println!("no config directory, set HOME or XDG_CONFIG_HOME");
+        std::process::exit(1);
+    };
+    let mut board = Leaderboard::load(&path).unwrap_or_else(|e| {
+        println!("could not read scores from {}: {e}", path.display());
+        std::process::exit(1);
+    });
+    if board.records.is_empty() {
+        println!("No scores yet, win a game to set one.");
+        return;
+    }
+    board.records.sort_by_key(|r| (r.setup.width * r.setup.height, r.setup.seed));
+    println!("{:<20}  {:<7}  {:<21}  {:>5}  {:>8}  {:>5}", "seed", "size", "algorithm", "braid", "time", "moves");
+    for record in &board.records {
+        let setup = &record.setup;
+        let algorithm = if setup.wrap { "Wrapped".to_string() } else { setup.algorithm.to_string() };
+        println!(
+            "{:<20}  {:<7}  {:<21}  {:>5.2}  {:>8.1?}  {:>5}",
+            setup.seed, format!("{}x{}", setup.width, setup.height), algorithm, setup.braid,
+            record.score.time(), record.score.moves
+        );
+    }
+}
+
+/// run_hex
+/// Plays a hex maze from the top left cell to the bottom right. Hex games
+/// only support the size, seed and export options.
+fn run_hex(options: &Options) {
+    let (cols, rows) = options.board_size().unwrap_or_else(hex_board_dims);
+    let mut rng = StdRng::seed_from_u64(options.seed.unwrap_or_else(rand::random));
+    let mut game = HexGame::new(cols, rows, &mut rng);
+    if let Some(path) = &options.export {
+        if let Err(e) = fs::write(path, game.render().join("\n") + "\n") {
+            println!("could not export to {}: {e}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
 
+    let _terminal = TerminalGuard::setup();
     let stdout = Term::buffered_stdout();
     loop {
-        if let Ok(c) = stdout.read_char() {
-            if let Err(_) = match c {
-                'w' => move_channel.send(StateEvent::Movement(UP)),
-                'a' => move_channel.send(StateEvent::Movement(LEFT)),
-                's' => move_channel.send(StateEvent::Movement(DOWN)),
-                'd' => move_channel.send(StateEvent::Movement(RIGHT)),
-                _ => move_channel.send(StateEvent::NoOP),
-            } {
-                break;
+        let status = match game.victory {
+            true => format!("You won in {} moves! Press q to quit.", game.moves),
+            false => format!("Moves: {}  w e a d z x to move, q to quit", game.moves),
+        };
+        print!("\x1b[H{}\x1b[K\r\n{status}\x1b[K", game.render().join("\x1b[K\r\n"));
+        let _ = io::stdout().flush();
+        match stdout.read_key() {
+            Ok(Key::Escape) | Ok(Key::Char('q')) | Err(_) => break,
+            Ok(Key::Char(c)) => {
+                if let Some(dir) = HexDirection::from_key(c) {
+                    game.move_position(dir);
+                }
             }
+            Ok(_) => (),
         }
     }
+}
 
-    // io::stdin().read(&mut [0u8]).unwrap();
+/// load_replay
+/// Loads a recording, exiting on error.
+fn load_replay(path: &Path) -> Replay {
+    Replay::load(path).unwrap_or_else(|e| {
+        println!("could not replay {}: {e}", path.display());
+        std::process::exit(1);
+    })
 }
 
-fn determine_board_size() -> usize {
-    let term_dims = term_size::dimensions();
-    if let None = term_dims {
-        println!("terminal size unknown");
+/// run_menu
+/// Shows the start menu and applies the picked settings to `options`.
+/// Returns false if the player quit from the menu.
+fn run_menu(options: &mut Options) -> bool {
+    let stdout = Term::buffered_stdout();
+    let mut menu = Menu::default();
+    loop {
+        let dims = term_size::dimensions().unwrap_or((80, 24));
+        let frame = menu.draw(dims);
+        match options.no_color {
+            true => print!("{}", strip_colors(&frame)),
+            false => print!("{frame}"),
+        }
+        let _ = io::stdout().flush();
+        let Ok(key) = stdout.read_key() else {
+            return false;
+        };
+        match menu.handle_key(&key) {
+            MenuAction::Continue => (),
+            MenuAction::Quit => return false,
+            MenuAction::Start(choice) => {
+                options.algo = Some(choice.algorithm);
+                options.size = Some(choice.size.map_or_else(|| determine_board_dims(options.compact, options.half_blocks), |s| (s, s)));
+                options.difficulty = Some(choice.difficulty);
+                return true;
+            }
+        }
+    }
+}
+
+/// open_editor
+/// Starts editing the maze in `path`, or a blank board of `size` if there
+/// isn't one yet. Exits if the file holds something else.
+fn open_editor(path: &Path, size: Option<(usize, usize)>) -> Editor {
+    match fs::read_to_string(path) {
+        Ok(text) => Editor::from_state(&import_ascii(&text).unwrap_or_else(|e| {
+            println!("could not edit {}: {e}", path.display());
+            std::process::exit(1);
+        })),
+        Err(_) => {
+            let (width, height) = size.unwrap_or_else(|| determine_board_dims(false, false));
+            let (width, height) = render_dims(width, height);
+            Editor::new(width, height)
+        }
+    }
+}
+
+/// run_editor
+/// Lets the player design a maze in the file given to --edit. Playing
+/// saves the design and loads it as if from --load-ascii. Returns false if
+/// the player quit.
+fn run_editor(mut editor: Editor, options: &mut Options) -> bool {
+    let Some(path) = options.edit.clone() else {
+        return false;
+    };
+    let stdout = Term::buffered_stdout();
+    loop {
+        let dims = term_size::dimensions().unwrap_or((80, 24));
+        let frame = editor.draw(dims);
+        match options.no_color {
+            true => print!("{}", strip_colors(&frame)),
+            false => print!("{frame}"),
+        }
+        let _ = io::stdout().flush();
+        let Ok(key) = stdout.read_key() else {
+            return false;
+        };
+        match editor.handle_key(&key) {
+            EditorAction::Continue => (),
+            EditorAction::Quit => return false,
+            EditorAction::Save(text) => editor.message = Some(match fs::write(&path, text) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(e) => format!("Save failed: {e}"),
+            }),
+            EditorAction::Play(state) => match fs::write(&path, export_ascii(state.board())) {
+                Ok(()) => {
+                    options.load_ascii = Some(path);
+                    return true;
+                }
+                Err(e) => editor.message = Some(format!("Save failed: {e}")),
+            },
+        }
+    }
+}
+
+/// new_game
+/// Generates a fresh game from the command line options and `seed`, or
+/// explains why the options can't make one. The setup is returned so it
+/// can be recorded.
+fn new_game(options: &Options, seed: u64) -> Result<(GameState, GameSetup), String> {
+    let size = options.board_size().unwrap_or_else(|| determine_board_dims(options.compact, options.half_blocks));
+    let setup = game_setup(options, size, seed)?;
+    Ok((setup.try_build()?, setup))
+}
+
+/// game_setup
+/// The setup for a `width` x `height` game, checking the goals fit. Extra
+/// goals only go on generation cells, see `GameState::scatter_goals`.
+fn game_setup(options: &Options, (width, height): (usize, usize), seed: u64) -> Result<GameSetup, String> {
+    if width * height < 2 {
+        return Err(format!("a {width}x{height} board has no room for a goal apart from the start"));
+    }
+    // Every generation cell but the start and the usual goal.
+    let room = ((width / 2 + 1) * (height / 2 + 1)).saturating_sub(2);
+    if options.goals.saturating_sub(1) > room {
+        return Err(format!(
+            "--goals {} needs {} cells besides the start and goal but a {width}x{height} board only has {room}",
+            options.goals, options.goals - 1
+        ));
+    }
+    Ok(GameSetup {
+        seed,
+        algorithm: options.algo.unwrap_or_default(),
+        width,
+        height,
+        braid: options.braid.or(options.difficulty.map(|d| d.braid_factor())).unwrap_or(0.0),
+        goals: options.goals,
+        items: options.items,
+        enemies: options.enemies,
+        wrap: options.wrap,
+        start: options.start,
+        goal: options.goal,
+        longest: options.longest,
+        portals: options.portals,
+        one_way: options.one_way,
+        ice: options.ice,
+        mud: options.mud,
+        diagonals: options.diagonals,
+        hardcore: options.hardcore,
+    })
+}
+
+/// run_bench
+/// Generates `count` mazes without rendering, seeded from `--seed` upwards,
+/// and prints how long they took and how hard they were. Boards default to
+/// the medium difficulty size so runs don't depend on the terminal.
+fn run_bench(options: &Options, count: usize) -> Result<(), String> {
+    let preset = Difficulty::default().size();
+    let size = options.board_size().unwrap_or((preset, preset));
+    let base = options.seed.unwrap_or(0);
+    let mut total = Duration::ZERO;
+    let (mut paths, mut solved, mut dead_ends) = (0, 0, 0);
+    for i in 0..count as u64 {
+        let setup = game_setup(options, size, base.wrapping_add(i))?;
+        let started = Instant::now();
+        let state = setup.try_build()?;
+        total += started.elapsed();
+        let stats = state.stats.unwrap_or_default();
+        if let Some(path) = stats.path_length {
+            paths += path;
+            solved += 1;
+        }
+        dead_ends += stats.dead_ends;
+    }
+
+    let algorithm = options.algo.unwrap_or_default();
+    println!("algorithm: {algorithm}  size: {}x{}  mazes: {count}", size.0, size.1);
+    println!("total: {total:.2?}  per maze: {:.2?}", total / count as u32);
+    println!(
+        "average path: {:.1}  average dead ends: {:.1}",
+        paths as f64 / solved.max(1) as f64,
+        dead_ends as f64 / count as f64
+    );
+    Ok(())
+}
+
+/// load_config
+/// The config file, or the defaults if there isn't one or it can't be
+/// read, saying why.
+fn load_config() -> Config {
+    let Some(path) = Config::default_path() else {
+        return Config::default();
+    };
+    Config::load(&path).unwrap_or_else(|e| {
+        eprintln!("ignoring {}: {e}", path.display());
+        Config::default()
+    })
+}
+
+/// load_key_bindings
+/// Movement keys from the config can be remapped again with MAZEME_KEYS,
+/// given in WASD order.
+fn load_key_bindings(keys: KeyBindings) -> KeyBindings {
+    match std::env::var("MAZEME_KEYS") {
+        Ok(layout) => keys.with_layout(&layout).unwrap_or_else(|| {
+            println!("MAZEME_KEYS must list exactly four keys not bound to anything else: up, left, down, right");
+            std::process::exit(1);
+        }),
+        Err(_) => keys,
+    }
+}
+
+/// load_ascii
+/// Loads a maze from a text file, exiting on error.
+fn load_ascii(path: &Path) -> GameState {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| {
+        println!("could not load {}: {e}", path.display());
+        std::process::exit(1);
+    });
+    import_ascii(&text).unwrap_or_else(|e| {
+        println!("could not load {}: {e}", path.display());
+        std::process::exit(1);
+    })
+}
+
+/// load_game
+/// Loads a saved game, exiting on error or if it doesn't match the
+/// board size given on the command line.
+fn load_game(path: &Path, size: Option<(usize, usize)>) -> GameState {
+    let state = GameState::load(path).unwrap_or_else(|e| {
+        println!("could not load {}: {e}", path.display());
         std::process::exit(1);
+    });
+    if let Some((width, height)) = size.map(|(w, h)| render_dims(w, h)) {
+        if (state.width(), state.height()) != (width, height) {
+            println!(
+                "could not load {}: saved board is {}x{}, expected {width}x{height}",
+                path.display(), state.width(), state.height()
+            );
+            std::process::exit(1);
+        }
     }
+    state
+}
 
-    let (wd, ht) = term_dims.unwrap();
+/// Options
+/// Settings read from the command line.
+#[derive(Parser)]
+#[command(name = "mazeme", about = "Generate a solvable maze in your terminal.")]
+struct Options {
+    /// Board width in cells, defaults to fit the terminal
+    #[arg(requires = "height", conflicts_with = "size")]
+    width: Option<usize>,
+    /// Board height in cells
+    height: Option<usize>,
+    /// Board size as N or WIDTHxHEIGHT, defaults to fit the terminal
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    size: Option<(usize, usize)>,
+    /// Seed for a reproducible maze, goals, items and enemies
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+    /// Play today's maze, the same for everyone on the same UTC day
+    #[arg(long, conflicts_with_all = [
+        "width", "size", "seed", "algo", "difficulty", "braid", "topology", "wrap", "goals", "items", "portals",
+        "one_way", "ice", "mud", "diagonals", "hardcore", "enemies", "start", "goal", "longest", "load",
+        "load_ascii", "edit", "replay", "join", "bench",
+    ])]
+    daily: bool,
+    /// Generation algorithm: backtracker, prim, kruskal, wilson, eller or division
+    #[arg(long, value_name = "ALGO")]
+    algo: Option<MazeAlgorithm>,
+    /// Preset size and braiding: easy, medium or hard
+    #[arg(long)]
+    difficulty: Option<Difficulty>,
+    /// Chance each dead end is opened into a loop, from 0.0 to 1.0
+    #[arg(long, value_name = "FACTOR", value_parser = parse_factor)]
+    braid: Option<f64>,
+    /// Grid shape: square or hex
+    #[arg(long)]
+    topology: Option<Topology>,
+    /// Wrap the edges, leaving one side comes back on the other
+    #[arg(long, conflicts_with_all = ["algo", "braid", "load", "load_ascii"])]
+    wrap: bool,
+    /// Start the player at X,Y instead of the top left corner
+    #[arg(long, value_name = "X,Y", value_parser = parse_position)]
+    start: Option<Position>,
+    /// Put the goal at X,Y instead of the bottom right corner
+    #[arg(long, value_name = "X,Y", value_parser = parse_position)]
+    goal: Option<Position>,
+    /// Put the goal on the cell furthest from the start
+    #[arg(long, conflicts_with = "goal")]
+    longest: bool,
+    /// Win within FACTOR times the fewest possible moves, 1.0 if not given
+    #[arg(long, value_name = "FACTOR", num_args = 0..=1, default_missing_value = "1.0", value_parser = parse_par,
+        conflicts_with_all = ["goals", "load", "load_ascii"])]
+    par: Option<f64>,
+    /// Number of goals that must all be collected to win
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_positive)]
+    goals: usize,
+    /// Number of coins to scatter through the maze
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    items: usize,
+    /// Number of portal pairs linking distant cells
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    portals: usize,
+    /// Number of one-way gates on the route to the goal
+    #[arg(long, value_name = "N", default_value_t = 0, conflicts_with = "goals")]
+    one_way: usize,
+    /// Number of slippery ice cells
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    ice: usize,
+    /// Number of mud cells, costing 2 to 4 moves to enter
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    mud: usize,
+    /// Allow diagonal moves with 7 9 1 3, laid out like the number pad
+    #[arg(long)]
+    diagonals: bool,
+    /// Never recross your own trail, and lose if it boxes you in
+    #[arg(long)]
+    hardcore: bool,
+    /// Number of roaming enemies
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    enemies: usize,
+    /// Only show cells within RADIUS of the player
+    #[arg(long, value_name = "RADIUS")]
+    fog: Option<usize>,
+    /// Close the fog in by a cell every SECS seconds, losing when it reaches you
+    #[arg(long, value_name = "SECS", requires = "fog",
+        value_parser = clap::value_parser!(u64).range(1..=MAX_FOG_CLOSING))]
+    fog_closing: Option<u64>,
+    /// Resume a saved game, saving again writes back to FILE
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "seed", "algo", "difficulty", "braid", "goals", "items", "portals", "one_way", "ice", "mud", "diagonals",
+        "hardcore", "enemies", "start", "goal", "longest",
+    ])]
+    load: Option<PathBuf>,
+    /// Play a maze drawn in the text file FILE, see --export
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "width", "size", "seed", "algo", "difficulty", "braid", "goals", "items", "portals", "one_way", "ice",
+        "mud", "diagonals", "hardcore", "enemies", "start", "goal", "longest", "load",
+    ])]
+    load_ascii: Option<PathBuf>,
+    /// Design a maze in the text file FILE, then play it
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "seed", "algo", "difficulty", "braid", "wrap", "goals", "items", "portals", "one_way", "ice", "mud",
+        "diagonals", "hardcore", "enemies", "start", "goal", "longest", "par", "load", "load_ascii", "record",
+        "replay", "host", "join", "animate_gen", "export", "png", "bench",
+    ])]
+    edit: Option<PathBuf>,
+    /// Generate N mazes without playing and print timing and stats
+    #[arg(long, value_name = "N", value_parser = parse_positive, conflicts_with_all = [
+        "load", "load_ascii", "replay", "record", "export", "png",
+    ])]
+    bench: Option<usize>,
+    /// Stream every frame to FILE, e.g. a FIFO, for `--spectate` to show
+    #[arg(long, value_name = "FILE")]
+    stream: Option<PathBuf>,
+    /// Show a game streamed on stdin by `--stream` and exit when it ends
+    #[arg(long, exclusive = true)]
+    spectate: bool,
+    /// Record the game to FILE so it can be replayed
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["load", "load_ascii", "replay"])]
+    record: Option<PathBuf>,
+    /// Play back a game recorded with --record
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "width", "size", "seed", "algo", "difficulty", "braid", "wrap", "goals", "items", "portals", "one_way",
+        "ice", "mud", "diagonals", "hardcore", "enemies", "fog", "fog_closing", "load", "load_ascii", "start", "goal",
+        "longest", "par", "export", "png",
+    ])]
+    replay: Option<PathBuf>,
+    /// Host a race over the network on ADDR, 0.0.0.0:7878 if not given
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = "0.0.0.0",
+        conflicts_with_all = [
+            "join", "goals", "enemies", "par", "load", "load_ascii", "record", "replay", "race", "export", "png",
+            "bench",
+        ])]
+    host: Option<String>,
+    /// Join a race hosted at ADDR, using the host's maze
+    #[arg(long, value_name = "ADDR", conflicts_with_all = [
+        "width", "size", "seed", "algo", "difficulty", "braid", "wrap", "goals", "items", "portals", "one_way",
+        "ice", "mud", "diagonals", "hardcore", "enemies", "start", "goal", "longest", "par", "load", "load_ascii",
+        "record", "replay", "race", "animate_gen", "export", "png", "bench",
+    ])]
+    join: Option<String>,
+    /// Race a second player, moved with the arrow keys, to the goal
+    #[arg(long, conflicts_with_all = [
+        "goals", "items", "enemies", "fog", "par", "load", "load_ascii", "record", "replay",
+    ])]
+    race: bool,
+    /// Count down SECS seconds before play begins, 3 by default in races
+    #[arg(long, value_name = "SECS")]
+    countdown: Option<u64>,
+    /// Show the maze being generated before play starts
+    #[arg(long, conflicts_with_all = ["wrap", "load", "load_ascii", "replay", "export", "png", "bench"])]
+    animate_gen: bool,
+    /// Watch a search (bfs, astar or dfs) explore the maze before playing
+    #[arg(long, value_name = "ALGO", conflicts_with_all = ["replay", "export", "png", "bench"])]
+    visualize_solve: Option<SearchAlgorithm>,
+    /// Write the maze to FILE as plain text and exit
+    #[arg(long, value_name = "FILE")]
+    export: Option<PathBuf>,
+    /// Draw the maze to a PNG image and exit
+    #[arg(long, value_name = "FILE")]
+    png: Option<PathBuf>,
+    /// Size of each PNG cell in pixels
+    #[arg(long, value_name = "N", default_value_t = 8, requires = "png", value_parser = clap::value_parser!(u32).range(1..))]
+    cell_px: u32,
+    /// Color theme: default, high-contrast or monochrome
+    #[arg(long, default_value_t = Theme::DEFAULT)]
+    theme: Theme,
+    /// Wall style: block or lines
+    #[arg(long, value_name = "STYLE", default_value_t = WallStyle::Block)]
+    walls: WallStyle,
+    /// Draw each cell one column wide, fitting larger mazes
+    #[arg(long)]
+    compact: bool,
+    /// Draw two rows of cells on each line with half blocks, fitting
+    /// taller mazes
+    #[arg(long)]
+    half_blocks: bool,
+    /// Draw the player as TEXT, e.g. an emoji, instead of an arrow
+    #[arg(long, value_name = "TEXT", value_parser = parse_glyph)]
+    player_glyph: Option<String>,
+    /// Draw goals as TEXT instead of a shaded block
+    #[arg(long, value_name = "TEXT", value_parser = parse_glyph)]
+    goal_glyph: Option<String>,
+    /// Show TEXT on the victory banner instead of "You Did It!"
+    #[arg(long, value_name = "TEXT")]
+    win_text: Option<String>,
+    /// Draw banners in the FIGlet font at FILE (.flf)
+    #[arg(long, value_name = "FILE", value_parser = parse_font)]
+    font: Option<String>,
+    /// Print the best times recorded for each maze and exit
+    #[arg(long, exclusive = true)]
+    scores: bool,
+    /// Draw without ANSI colors, the default when stdout isn't a terminal
+    #[arg(long)]
+    no_color: bool,
+    /// Move every MS milliseconds while a direction is held, 0 leaves
+    /// repeating to the terminal
+    #[arg(long, value_name = "MS", default_value_t = 80)]
+    repeat_ms: u64,
+    /// Hide the controls legend under the maze
+    #[arg(long)]
+    no_help: bool,
+    /// Print the summary of the run on exit as JSON
+    #[arg(long)]
+    stats_json: bool,
+    /// Don't ring the terminal bell on blocked moves and victory
+    #[arg(long)]
+    quiet: bool,
+    /// Also play with a gamepad, needs the gamepad feature
+    #[arg(long)]
+    gamepad: bool,
+}
 
-    if wd/3 < ht {
-        return (wd/3)-2;
+impl Options {
+    /// board_size
+    /// The size given on the command line or by the difficulty preset.
+    fn board_size(&self) -> Option<(usize, usize)> {
+        self.size.or(self.difficulty.map(|d| (d.size(), d.size())))
     }
-    ht-2
+
+    /// parse_valid
+    /// Reads the command line, exiting with usage on bad input. Positional
+    /// WIDTH HEIGHT are folded into `size`, and anything not given is
+    /// taken from `config`.
+    fn parse_valid(config: &Config) -> Options {
+        let matches = Options::command().get_matches();
+        let mut options = Options::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let (Some(w), Some(h)) = (options.width, options.height) {
+            if w == 0 || h == 0 {
+                Options::command().error(ErrorKind::ValueValidation, "WIDTH and HEIGHT must be at least 1").exit();
+            }
+            options.size = Some((w, h));
+        }
+        if options.topology == Some(Topology::Hex) {
+            let unsupported = [
+                ("--algo", options.algo.is_some()),
+                ("--braid", options.braid.is_some()),
+                ("--wrap", options.wrap),
+                ("--goals", options.goals != 1),
+                ("--items", options.items != 0),
+                ("--portals", options.portals != 0),
+                ("--one-way", options.one_way != 0),
+                ("--ice", options.ice != 0),
+                ("--mud", options.mud != 0),
+                ("--diagonals", options.diagonals),
+                ("--hardcore", options.hardcore),
+                ("--enemies", options.enemies != 0),
+                ("--fog", options.fog.is_some()),
+                ("--load", options.load.is_some()),
+                ("--load-ascii", options.load_ascii.is_some()),
+                ("--edit", options.edit.is_some()),
+                ("--bench", options.bench.is_some()),
+                ("--record", options.record.is_some()),
+                ("--replay", options.replay.is_some()),
+                ("--png", options.png.is_some()),
+                ("--animate-gen", options.animate_gen),
+                ("--visualize-solve", options.visualize_solve.is_some()),
+                ("--start", options.start.is_some()),
+                ("--goal", options.goal.is_some()),
+                ("--longest", options.longest),
+                ("--par", options.par.is_some()),
+                ("--race", options.race),
+                ("--host", options.host.is_some()),
+                ("--join", options.join.is_some()),
+                ("--countdown", options.countdown.is_some()),
+                ("--stats-json", options.stats_json),
+                ("--gamepad", options.gamepad),
+                ("--stream", options.stream.is_some()),
+                ("--compact", options.compact),
+                ("--half-blocks", options.half_blocks),
+            ];
+            if let Some((flag, _)) = unsupported.iter().find(|(_, given)| *given) {
+                let msg = format!("{flag} is not supported with --topology hex");
+                Options::command().error(ErrorKind::ArgumentConflict, msg).exit();
+            }
+        }
+        let defaulted = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+        if defaulted("theme") {
+            options.theme = config.theme.unwrap_or(options.theme);
+        }
+        if defaulted("walls") {
+            options.walls = config.walls.unwrap_or(options.walls);
+        }
+        if defaulted("repeat_ms") {
+            options.repeat_ms = config.repeat_ms.unwrap_or(options.repeat_ms);
+        }
+        if !options.race {
+            options.fog = options.fog.or(config.fog);
+        }
+        options.player_glyph = options.player_glyph.take().or(config.player_glyph.clone());
+        options.goal_glyph = options.goal_glyph.take().or(config.goal_glyph.clone());
+        options.compact |= config.compact;
+        options.half_blocks |= config.half_blocks;
+        options.no_color |= config.no_color;
+        options.no_help |= config.no_help;
+        options.quiet |= config.quiet;
+        options.stats_json |= config.stats_json;
+        // Loaded, replayed and joined mazes are already made, and the daily
+        // maze must be the same for everyone.
+        let new_maze = options.load.is_none() && options.load_ascii.is_none() && options.replay.is_none()
+            && options.join.is_none() && !options.daily;
+        if new_maze {
+            if options.board_size().is_none() {
+                options.size = config.size();
+            }
+            options.algo = options.algo.or(config.algo);
+            options.difficulty = options.difficulty.or(config.difficulty);
+            options.braid = options.braid.or(config.braid);
+        }
+        options
+    }
+}
+
+const SAVE_FILE: &str = "mazeme_save.json";
+/// --animate-gen shows at most this many steps, one every delay.
+const GENERATION_FRAMES: usize = 120;
+const GENERATION_FRAME_DELAY: Duration = Duration::from_millis(20);
+/// --visualize-solve shows at most this many steps, then holds the path.
+const SEARCH_FRAMES: usize = 150;
+const SEARCH_FRAME_DELAY: Duration = Duration::from_millis(30);
+const SEARCH_RESULT_DELAY: Duration = Duration::from_millis(1500);
+/// Set while a game is played, when Ctrl-C quits it like the quit key.
+static PLAYING: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// parse_size
+/// Accepts `N` for a square board or `WIDTHxHEIGHT`.
+fn parse_size(s: &str) -> Result<(usize, usize), String> {
+    let (w, h) = s.split_once(['x', 'X']).unwrap_or((s, s));
+    match (w.parse::<usize>(), h.parse::<usize>()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(format!("{s:?} is not a size, expected N or WIDTHxHEIGHT")),
+    }
+}
+
+/// parse_position
+/// Accepts `X,Y` board cell coordinates, counted from 0 at the top left.
+fn parse_position(s: &str) -> Result<Position, String> {
+    s.split_once(',')
+        .and_then(|(x, y)| Some(Position::new(x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or_else(|| format!("{s:?} is not a position, expected X,Y"))
+}
+
+/// parse_par
+fn parse_par(s: &str) -> Result<f64, String> {
+    s.parse::<f64>().ok().filter(|f| f.is_finite() && *f >= 1.0)
+        .ok_or_else(|| format!("{s:?} is not a par factor, expected a number of at least 1.0"))
+}
+
+/// parse_factor
+fn parse_factor(s: &str) -> Result<f64, String> {
+    s.parse::<f64>().ok().filter(|f| (0.0..=1.0).contains(f))
+        .ok_or_else(|| format!("{s:?} is not a factor between 0.0 and 1.0"))
+}
+
+/// parse_font
+/// Reads a FIGlet font, checking that it parses.
+fn parse_font(s: &str) -> Result<String, String> {
+    let font = fs::read_to_string(s).map_err(|e| format!("could not read {s}: {e}"))?;
+    FIGfont::from_content(&font).map_err(|e| format!("{s} is not a FIGlet font: {e}"))?;
+    Ok(font)
+}
+
+/// parse_glyph
+/// Accepts any text that takes up some room on screen.
+fn parse_glyph(s: &str) -> Result<String, String> {
+    match s.trim().is_empty() || s.chars().any(char::is_control) {
+        true => Err(format!("{s:?} is not a glyph, expected something visible")),
+        false => Ok(s.to_string()),
+    }
+}
+
+/// parse_positive
+fn parse_positive(s: &str) -> Result<usize, String> {
+    s.parse::<usize>().ok().filter(|&n| n > 0)
+        .ok_or_else(|| format!("{s:?} is not a number of at least 1"))
+}
+
+/// hex_board_dims
+/// The most hex cells that fit the terminal, each drawn 4 columns wide and
+/// 2 rows high, with a line left for the status.
+fn hex_board_dims() -> (usize, usize) {
+    let Some((wd, ht)) = term_size::dimensions() else {
+        println!("terminal size unknown");
+        std::process::exit(1);
+    };
+    ((wd.saturating_sub(5) / 4).max(1), (ht.saturating_sub(2) / 2).max(1))
+}
+
+/// determine_board_dims
+/// The largest board that fits the terminal without scrolling, leaving room
+/// for the borders and status line. Boards render at odd sizes, so even
+/// sizes are rounded down. Cells are a column wide if `compact`, else 3,
+/// and two rows share each line with `half_blocks`.
+fn determine_board_dims(compact: bool, half_blocks: bool) -> (usize, usize) {
+    let Some((wd, ht)) = term_size::dimensions() else {
+        println!("terminal size unknown");
+        std::process::exit(1);
+    };
+    let odd = |n: usize| if n.is_multiple_of(2) { n.saturating_sub(1).max(1) } else { n };
+    let cell_width = if compact { 1 } else { 3 };
+    let rows_per_line = if half_blocks { 2 } else { 1 };
+    (odd(wd.saturating_sub(2) / cell_width), odd(ht.saturating_sub(4) * rows_per_line))
 }
@@ -0,0 +1,116 @@
+///
+/// Start Menu Module
+///
+use console::Key;
+
+use crate::game_state::gen::{Difficulty, MazeAlgorithm};
+
+const ESC: &str = "\x1b";
+const SIZES: [Option<usize>; 5] = [None, Some(11), Some(21), Some(41), Some(81)];
+const ROWS: usize = 3;
+
+/// MenuChoice
+/// The settings picked on the start menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuChoice {
+    pub algorithm: MazeAlgorithm,
+    /// Board width and height, None to fit the terminal.
+    pub size: Option<usize>,
+    pub difficulty: Difficulty,
+}
+
+/// MenuAction
+/// What the caller should do after a key press.
+pub enum MenuAction {
+    Continue,
+    Start(MenuChoice),
+    Quit,
+}
+
+/// Menu
+/// Up and down pick a row, left and right cycle its value, Enter starts
+/// and q or Escape quits.
+pub struct Menu {
+    row: usize,
+    algorithm: usize,
+    size: usize,
+    difficulty: usize,
+}
+
+impl Default for Menu {
+    fn default() -> Menu {
+        Menu {
+            row: 0,
+            algorithm: 0,
+            size: 0,
+            difficulty: Difficulty::ALL.iter().position(|&d| d == Difficulty::default()).unwrap_or(0),
+        }
+    }
+}
+
+impl Menu {
+    /// choice
+    /// The settings currently selected.
+    pub fn choice(&self) -> MenuChoice {
+        MenuChoice {
+            algorithm: MazeAlgorithm::ALL[self.algorithm],
+            size: SIZES[self.size],
+            difficulty: Difficulty::ALL[self.difficulty],
+        }
+    }
+
+    /// handle_key
+    pub fn handle_key(&mut self, key: &Key) -> MenuAction {
+        match key {
+            Key::ArrowUp => self.row = (self.row + ROWS - 1) % ROWS,
+            Key::ArrowDown => self.row = (self.row + 1) % ROWS,
+            Key::ArrowLeft => self.cycle(false),
+            Key::ArrowRight => self.cycle(true),
+            Key::Enter => return MenuAction::Start(self.choice()),
+            Key::Escape | Key::Char('q') => return MenuAction::Quit,
+            _ => (),
+        }
+        MenuAction::Continue
+    }
+
+    fn cycle(&mut self, forward: bool) {
+        let (value, len) = match self.row {
+            0 => (&mut self.algorithm, MazeAlgorithm::ALL.len()),
+            1 => (&mut self.size, SIZES.len()),
+            _ => (&mut self.difficulty, Difficulty::ALL.len()),
+        };
+        *value = if forward { (*value + 1) % len } else { (*value + len - 1) % len };
+    }
+
+    /// draw
+    /// The menu centered on a `wd` x `ht` screen, as one frame of escape codes.
+    pub fn draw(&self, (wd, ht): (usize, usize)) -> String {
+        let choice = self.choice();
+        let size = match choice.size {
+            Some(s) => format!("{s} x {s}"),
+            None => "Fit terminal".to_string(),
+        };
+        let rows = [
+            ("Algorithm", choice.algorithm.to_string()),
+            ("Size", size),
+            ("Difficulty", choice.difficulty.to_string()),
+        ];
+
+        let top = ht.saturating_sub(rows.len() + 4) / 2;
+        let left = wd.saturating_sub(40) / 2;
+        let mut frame = format!("{ESC}[2J{ESC}[{top};{left}H{ESC}[1mMazeMe{ESC}[0m");
+        for (i, (label, value)) in rows.iter().enumerate() {
+            frame.push_str(format!("{ESC}[{row};{left}H", row = top + 2 + i).as_str());
+            if i == self.row {
+                frame.push_str(format!("{ESC}[7m{label:>12}  ◀ {value} ▶{ESC}[0m").as_str());
+            } else {
+                frame.push_str(format!("{label:>12}    {value}").as_str());
+            }
+        }
+        frame.push_str(format!(
+            "{ESC}[{row};{left}H{ESC}[2m↑↓ select  ←→ change  Enter start  q quit{ESC}[0m",
+            row = top + 3 + rows.len()
+        ).as_str());
+        frame
+    }
+}
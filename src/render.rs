@@ -1,80 +1,961 @@
+mod glyphs;
+mod png;
+#[cfg(feature = "serde")]
+pub mod stream;
+mod theme;
+
 use std::io::{self, Write};
-use std::sync::mpsc;
+use std::sync::{mpsc, Mutex};
 use std::thread;
+use std::time::Duration;
 use figlet_rs::FIGfont;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::game_state::stats::MazeStats;
+use crate::game_state::{SYMBOL, GOAL, WALL, GameState, Movement, Position};
 
-use crate::game_state::{SYMBOL, GOAL, GameState};
+pub use self::glyphs::{fit_cell, Glyphs, WallStyle};
+use self::glyphs::{take_width, wall_lines};
+pub use self::png::{export_png, export_png_with, PngStyle};
+pub use self::theme::Theme;
 
 const ESC: &str = "\x1b";
+const BELL: &str = "\x07";
+// Terminals merge bells rung back to back, so the victory tune is spaced out.
+const VICTORY_BELLS: usize = 3;
+const VICTORY_BELL_GAP: Duration = Duration::from_millis(150);
 const BORDER: char = '░';
+const TRAIL: char = '·';
+const ITEM: char = '$';
+const ENEMY: char = '✖';
+const PORTAL: char = '◎';
+const ICE: char = '≈';
+const HINT: char = '◇';
+const SOLUTION: char = '•';
+const FRONTIER: char = '○';
+const EXPLORED: char = '∙';
+// Without color the goal and player can't rely on their highlight.
+const PLAIN_PLAYER: &str = " @ ";
+const PLAIN_GOAL: &str = "[G]";
+const PLAIN_RIVAL: &str = " & ";
+/// Every cell width zooming steps through, widest first.
+pub const CELL_WIDTHS: [usize; 3] = [3, 2, 1];
 
-pub fn start_render(rx: mpsc::Receiver<GameState>, board_size: usize) {
-    let (wd, ht) = term_size::dimensions().unwrap_or((board_size+ 1, board_size + 1));
-    let (draw_x, draw_y) = ((wd - (3 * board_size + 1)) / 2, (ht - board_size + 1) / 2);
-
-    thread::spawn(move || {
-        loop {
-            if let Ok(state) = rx.recv() {
-                let mut frame = format!("{ESC}[2J{ESC}[{y};{x}H", y=draw_y, x=draw_x);
-
-                // Draw Top Border
-                for i in 0..(3 * board_size + 2) {
-                    if i == (3*board_size+2)/2 - 1 {
-                        frame.push_str(format!("{ESC}[4m{ESC}[;35m{:03}{ESC}[0m{ESC}[24m", state.time_remaining).as_str());
-                    } else if i == (3*board_size+2)/2 || i == (3*board_size+2)/2 + 1 {
-                        // Do Nothing
-                    } else {
-                        frame.push_str(format!("{BORDER}").as_str());
-                    }
-                }
+/// RenderConfig
+/// How frames are drawn, fixed for the whole game apart from `zoom`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderConfig {
+    pub theme: Theme,
+    /// When false all SGR sequences are stripped from each frame.
+    pub color: bool,
+    /// Ring the terminal bell on blocked moves and victory.
+    pub sound: bool,
+    /// Replaces "You Did It!" on the victory banner.
+    pub win_text: Option<String>,
+    /// The text of a FIGlet `.flf` font for banners, None for the standard one.
+    pub font: Option<String>,
+    /// A line of controls shown under the status line, if there's room.
+    pub legend: Option<String>,
+    /// A line shown above the maze, like `GameSetup::title`.
+    pub header: Option<String>,
+    pub glyphs: Glyphs,
+    pub walls: WallStyle,
+    /// Draw each cell one column wide instead of three, fitting boards
+    /// three times as wide.
+    pub compact: bool,
+    /// Draw two board rows on each terminal row with half block
+    /// characters, fitting boards twice as tall.
+    pub half_blocks: bool,
+    /// Steps zoomed out from the usual width, following `GameState::zoom`.
+    pub zoom: usize,
+}
 
-                frame.push_str(format!("{ESC}[E{ESC}[{x}G", x=draw_x).as_str());
-                // Draw each row
-                for row in state.board.iter() {
-                    // Left Border
-                    frame.push_str(format!("{BORDER}").as_str());
-                    for v in row.iter() {
-                        match *v {
-                            SYMBOL => frame.push_str(format!("◀◆▶").as_str()),
-                            GOAL => frame.push_str(format!("{ESC}[35m{v}{v}{v}{ESC}[0m").as_str()),
-                            _ => frame.push_str(format!("{v}{v}{v}").as_str()),
-                        }
-                    }
-                    // Right Border
-                    frame.push_str(format!("{BORDER}\n").as_str());
-                    frame.push_str(format!("{ESC}[{x}G", x=draw_x).as_str());
-                }
+impl RenderConfig {
+    /// cell_width
+    /// Terminal columns each board cell takes up. Zooming out from the
+    /// widest cells steps through `CELL_WIDTHS`, wrapping back round.
+    pub fn cell_width(&self) -> usize {
+        let usual = if self.compact { CELL_WIDTHS.len() - 1 } else { 0 };
+        CELL_WIDTHS[(usual + self.zoom) % CELL_WIDTHS.len()]
+    }
+
+    /// rows_per_line
+    /// Board rows drawn on each terminal row.
+    pub fn rows_per_line(&self) -> usize {
+        if self.half_blocks { 2 } else { 1 }
+    }
+}
+
+impl Default for RenderConfig {
+    fn default() -> RenderConfig {
+        RenderConfig {
+            theme: Theme::DEFAULT,
+            color: true,
+            sound: true,
+            win_text: None,
+            font: None,
+            legend: None,
+            header: None,
+            glyphs: Glyphs::default(),
+            walls: WallStyle::Block,
+            compact: false,
+            half_blocks: false,
+            zoom: 0,
+        }
+    }
+}
+
+/// TerminalGuard
+/// Owns the terminal for the lifetime of a game, drawing on the alternate
+/// screen with the cursor hidden so the scrollback is left untouched.
+/// Dropping restores both, and also happens while unwinding, so a panic
+/// leaves a usable prompt. The terminal's settings are put back too, in
+/// case the game ends while another thread is reading a key in raw mode.
+pub struct TerminalGuard(());
+
+#[cfg(unix)]
+type TtySettings = Option<libc::termios>;
+#[cfg(not(unix))]
+type TtySettings = ();
+
+/// The settings to put back once the open `TerminalGuard` is done, None
+/// while there isn't one.
+static TAKEN: Mutex<Option<TtySettings>> = Mutex::new(None);
+
+impl TerminalGuard {
+    /// setup
+    pub fn setup() -> TerminalGuard {
+        print!("{ESC}[?1049h{ESC}[?25l{ESC}[2J");
+        let _ = io::stdout().flush();
+        *TAKEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(tty_settings());
+        TerminalGuard(())
+    }
+
+    /// restore
+    /// Puts the terminal back the way the open guard found it, if there is
+    /// one, like dropping it does but from anywhere, e.g. on Ctrl-C. Waits
+    /// for any frame being written to finish, and returns holding stdout so
+    /// no more are.
+    pub fn restore() -> io::StdoutLock<'static> {
+        let mut out = io::stdout().lock();
+        if let Some(settings) = TAKEN.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            // Reset colors, show the cursor and go back to the main screen.
+            let _ = write!(out, "{ESC}[0m{ESC}[?25h{ESC}[?1049l");
+            let _ = out.flush();
+            set_tty_settings(settings);
+        }
+        out
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        drop(TerminalGuard::restore());
+    }
+}
+
+/// tty_settings
+/// The settings of the terminal on stdin, None if it isn't one.
+#[cfg(unix)]
+fn tty_settings() -> TtySettings {
+    let mut settings = std::mem::MaybeUninit::uninit();
+    // SAFETY: `tcgetattr` fills in `settings` when it succeeds.
+    unsafe {
+        (libc::isatty(libc::STDIN_FILENO) == 1 && libc::tcgetattr(libc::STDIN_FILENO, settings.as_mut_ptr()) == 0)
+            .then(|| settings.assume_init())
+    }
+}
+
+#[cfg(not(unix))]
+fn tty_settings() -> TtySettings {}
+
+/// set_tty_settings
+#[cfg(unix)]
+fn set_tty_settings(settings: TtySettings) {
+    if let Some(settings) = settings {
+        // SAFETY: `settings` was filled in by `tcgetattr` on stdin.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &settings) };
+    }
+}
+
+#[cfg(not(unix))]
+fn set_tty_settings(_: TtySettings) {}
+
+/// export_ascii
+/// The maze as plain monospace text, laid out like the screen with 3 wide
+/// cells and a border, but without any escape codes.
+pub fn export_ascii(board: &[Vec<char>]) -> String {
+    let width = board.first().map(|row| row.len()).unwrap_or(0);
+    let border_row = BORDER.to_string().repeat(3 * width + 2);
+
+    let mut out = format!("{border_row}\n");
+    for row in board {
+        out.push(BORDER);
+        for v in row {
+            match *v {
+                SYMBOL => out.push_str(player_sprite(None)),
+                _ => out.push_str(format!("{v}{v}{v}").as_str()),
+            }
+        }
+        out.push(BORDER);
+        out.push('\n');
+    }
+    out.push_str(&border_row);
+    out.push('\n');
+    out
+}
+
+/// import_ascii
+/// Reads a maze written by `export_ascii` back into a game. Hand drawn
+/// mazes may instead use one character per cell, without a border, with
+/// the player as `●`. Either way walls are `░` or `#`, open cells spaces
+/// and the goal `▓`, and there must be one of each endpoint with a route
+/// between them.
+pub fn import_ascii(text: &str) -> Result<GameState, String> {
+    let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+    let mut board = match text.contains(SYMBOL) {
+        true => lines,
+        false => unframe(&lines)?,
+    };
+    let width = board.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err("the maze is empty".to_string());
+    }
+    let (mut starts, mut goals) = (vec![], vec![]);
+    for (y, row) in board.iter_mut().enumerate() {
+        if row.len() != width {
+            return Err(format!("row {} is {} cells wide, expected {width}", y + 1, row.len()));
+        }
+        for (x, v) in row.iter_mut().enumerate() {
+            match *v {
+                SYMBOL => starts.push(Position::new(x, y)),
+                GOAL => goals.push(Position::new(x, y)),
+                '#' => *v = WALL,
+                WALL | ' ' => (),
+                c => return Err(format!("unknown character {c:?} at row {}, column {}", y + 1, x + 1)),
+            }
+        }
+    }
+    let (&[start], &[goal]) = (&starts[..], &goals[..]) else {
+        return Err(format!("expected one start and one goal, found {} and {}", starts.len(), goals.len()));
+    };
+    let state = GameState::from_board(board, start, goal);
+    match state.solution() {
+        Some(_) => Ok(state),
+        None => Err("the goal can't be reached from the start".to_string()),
+    }
+}
 
-                // Draw Bottom Border
-                for _ in 0..(3 * board_size + 2) {
-                    frame.push_str(format!("{BORDER}").as_str());
+/// unframe
+/// The render board inside an `export_ascii` border, one char per 3 wide
+/// cell.
+fn unframe(lines: &[Vec<char>]) -> Result<Vec<Vec<char>>, String> {
+    let framed = |line: &Vec<char>| line.len() >= 2 && line[0] == BORDER && line[line.len() - 1] == BORDER;
+    let (Some(top), Some(bottom)) = (lines.first(), lines.last()) else {
+        return Err("the maze is empty".to_string());
+    };
+    if lines.len() < 3 || [top, bottom].iter().any(|line| !line.iter().all(|&c| c == BORDER)) {
+        return Err("the maze must be surrounded by a border".to_string());
+    }
+    let player: Vec<char> = player_sprite(None).chars().collect();
+    lines[1..lines.len() - 1].iter().enumerate().map(|(y, line)| {
+        if !framed(line) || line.len() != top.len() || (line.len() - 2) % 3 != 0 {
+            return Err(format!("row {} doesn't line up with the border", y + 1));
+        }
+        line[1..line.len() - 1].chunks(3).enumerate().map(|(x, cell)| match cell {
+            _ if cell == &player[..] => Ok(SYMBOL),
+            &[a, b, c] if a == b && b == c => Ok(a),
+            _ => Err(format!("{:?} at row {}, column {} is not a cell", String::from_iter(cell), y + 1, x + 1)),
+        }).collect()
+    }).collect()
+}
+
+/// render_minimap
+/// The whole board downsampled into the top right corner, one char per
+/// cell when it fits in a quarter of the screen, otherwise each char covers
+/// a square block of cells. Blocks draw as wall when most of their cells
+/// are walls, and any block holding the player or goal shows it instead.
+pub fn render_minimap(state: &GameState, theme: &Theme, (wd, ht): (usize, usize)) -> String {
+    let (width, height) = (state.width(), state.height());
+    let (max_w, max_h) = ((wd / 4).max(1), (ht / 4).max(1));
+    let scale = width.div_ceil(max_w).max(height.div_ceil(max_h)).max(1);
+    let (map_w, map_h) = (width.div_ceil(scale), height.div_ceil(scale));
+    let left = wd.saturating_sub(map_w) + 1;
+
+    let mut out = String::new();
+    for my in 0..map_h {
+        out.push_str(format!("{ESC}[{row};{left}H", row = my + 1).as_str());
+        for mx in 0..map_w {
+            let (ys, xs) = (my * scale..((my + 1) * scale).min(height), mx * scale..((mx + 1) * scale).min(width));
+            let contains = |p: Position| ys.contains(&p.y()) && xs.contains(&p.x());
+            if contains(state.position) {
+                out.push_str(&theme.paint(&Theme::with("7", theme.player), &SYMBOL.to_string()));
+            } else if state.goals.iter().any(|&g| contains(g)) {
+                out.push_str(&theme.paint(theme.goal, &GOAL.to_string()));
+            } else {
+                let walls = ys.clone().flat_map(|y| xs.clone().map(move |x| (x, y)))
+                    .filter(|&(x, y)| state.board[y][x] == WALL)
+                    .count();
+                let cells = ys.len() * xs.len();
+                match walls * 2 > cells {
+                    true => out.push_str(&theme.paint(theme.wall, &WALL.to_string())),
+                    false => out.push(' '),
                 }
+            }
+        }
+    }
+    out
+}
+
+/// player_sprite
+/// The 3 wide player glyph, pointing the way the player last moved.
+fn player_sprite(facing: Option<Movement>) -> &'static str {
+    match facing {
+        Some(Movement::UP) => " ▲ ",
+        Some(Movement::DOWN) => " ▼ ",
+        Some(Movement::LEFT) => " ◀ ",
+        Some(Movement::RIGHT) => " ▶ ",
+        Some(Movement::UP_LEFT) => " ◤ ",
+        Some(Movement::UP_RIGHT) => " ◥ ",
+        Some(Movement::DOWN_LEFT) => " ◣ ",
+        Some(Movement::DOWN_RIGHT) => " ◢ ",
+        None => "◀◆▶",
+    }
+}
+
+/// draw_banner
+/// Writes `message` in large FIGlet letters centered on screen, with an
+/// optional plain `subtitle` line beneath it.
+fn draw_banner(
+    frame: &mut String,
+    message: &str,
+    subtitle: Option<&str>,
+    font: Option<&str>,
+    (wd, ht): (usize, usize),
+) {
+    let lines = banner_lines(message, font, (wd, ht.saturating_sub(usize::from(subtitle.is_some()))));
+    let m_w = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+    let m_h = lines.len() + usize::from(subtitle.is_some());
+    let (x, y) = (centered(m_w, wd), centered(m_h, ht));
+    for (i, l) in lines.iter().enumerate() {
+        frame.push_str(format!("{ESC}[{row};{x}H{l}", row = y + i).as_str());
+    }
+    if let Some(subtitle) = subtitle {
+        let subtitle = take_width(subtitle, wd);
+        let x = centered(subtitle.width(), wd);
+        frame.push_str(format!("{ESC}[{row};{x}H{subtitle}", row = y + lines.len()).as_str());
+    }
+    frame.push_str(format!("{ESC}[{ht};{wd}H").as_str());
+}
+
+/// centered
+/// The 1-based column or row that centers `len` cells in `span`.
+fn centered(len: usize, span: usize) -> usize {
+    span.saturating_sub(len) / 2 + 1
+}
+
+/// banner_lines
+/// `message` as FIGlet art in `font`, falling back to the standard font
+/// and then to the plain message, cut short if need be, when it won't fit
+/// in `wd` x `ht`.
+fn banner_lines(message: &str, font: Option<&str>, (wd, ht): (usize, usize)) -> Vec<String> {
+    let fonts = font.and_then(|f| FIGfont::from_content(f).ok()).into_iter().chain(FIGfont::standard().ok());
+    for font in fonts {
+        let Some(art) = font.convert(message) else {
+            continue;
+        };
+        let lines: Vec<String> = art.to_string().lines().map(str::to_string).collect();
+        if lines.len() <= ht && lines.iter().all(|l| l.width() <= wd) {
+            return lines;
+        }
+    }
+    vec![take_width(message, wd)]
+}
+
+/// Viewport
+/// The window of board cells drawn on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Terminal columns each cell takes up.
+    pub cell_width: usize,
+    /// Board rows drawn on each terminal row.
+    pub rows_per_line: usize,
+}
+
+impl Viewport {
+    /// centered
+    /// The largest window of 3 wide cells that fits a `wd` x `ht`
+    /// terminal, see `centered_with`.
+    pub fn centered(state: &GameState, dims: (usize, usize)) -> Viewport {
+        Viewport::centered_with(state, dims, 3, 1)
+    }
+
+    /// centered_with
+    /// The largest window of `cell_width` wide cells, `rows_per_line` of
+    /// them to a terminal row, that fits a `wd` x `ht` terminal, centered
+    /// on the player, or halfway between both in a race, and clamped so it
+    /// never scrolls past the board edges. Room is left for the borders
+    /// and status line.
+    pub fn centered_with(state: &GameState, (wd, ht): (usize, usize), cell_width: usize, rows_per_line: usize) -> Viewport {
+        let width = state.width().min((wd.saturating_sub(2) / cell_width).max(1));
+        let height = state.height().min(ht.saturating_sub(4).max(1) * rows_per_line);
+        let other = state.rival.map_or(state.position, |rival| rival.position);
+        let (x, y) = ((state.position.x() + other.x()) / 2, (state.position.y() + other.y()) / 2);
+        let left = x.saturating_sub(width / 2).min(state.width() - width);
+        let top = y.saturating_sub(height / 2).min(state.height() - height);
+        Viewport { left, top, width, height, cell_width, rows_per_line }
+    }
 
-                if state.victory || state.failure{
-                    let ffont = FIGfont::standard().unwrap();
-                    let mut message = "You Did It!";
-                    if state.failure {
-                        message = " You Lose!";
-                    }
-                    if let Some(msg) = ffont.convert(message) {
-                        let mut m_w = msg.to_string().lines().map(|s| s.len()).max().unwrap_or(1);
-                        let mut m_h = msg.height as usize;
-                        if m_w > wd || m_h > ht {
-                            m_w = 0;
-                            m_h = 0;
-                        }
-                        let midpoint = ((wd - m_w) / 2, (ht - m_h) / 2);
-                        for (i, l) in msg.to_string().lines().enumerate() {
-                            frame.push_str(format!("{ESC}[{ht};{w}H", w = midpoint.0, ht = midpoint.1 + i).as_str());
-                            frame.push_str(format!("{}", l).as_str());
-                            frame.push_str(format!("{ESC}[{ht};{wd}H").as_str());
-                        }
-                    }
+    /// lines
+    /// Terminal rows the window takes up, not counting the borders.
+    pub fn lines(&self) -> usize {
+        self.height.div_ceil(self.rows_per_line)
+    }
+
+    /// origin
+    /// Screen column and row of the top left border corner that centers
+    /// the view. Views larger than the terminal start at the top left.
+    pub fn origin(&self, (wd, ht): (usize, usize)) -> (usize, usize) {
+        (wd.saturating_sub(self.cell_width * self.width + 1) / 2, ht.saturating_sub(self.lines()).div_ceil(2))
+    }
+}
+
+/// strip_colors
+/// Removes every SGR (`ESC [ .. m`) sequence from `frame`, leaving cursor
+/// movement intact so the layout is unchanged.
+pub fn strip_colors(frame: &str) -> String {
+    let mut out = String::with_capacity(frame.len());
+    let mut rest = frame;
+    while let Some(start) = rest.find("\x1b[") {
+        out.push_str(&rest[..start]);
+        let seq = &rest[start + 2..];
+        match seq.find(|c: char| !(c.is_ascii_digit() || c == ';')) {
+            Some(end) if seq[end..].starts_with('m') => rest = &seq[end + 1..],
+            _ => {
+                out.push_str(&rest[start..start + 2]);
+                rest = seq;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// cell_glyph
+/// The text drawn for board cell `pos`, including its colors, padded to
+/// exactly 3 columns however wide its symbols are. Cells too wide to pad
+/// are cut short, losing their colors. Narrower cells are cut down by
+/// `narrow`.
+fn cell_glyph(state: &GameState, config: &RenderConfig, pos: Position) -> String {
+    let glyph = styled_glyph(state, config, pos);
+    let plain = strip_colors(&glyph);
+    let cell = match plain.width() {
+        3 => glyph,
+        w if w < 3 => glyph + &" ".repeat(3 - w),
+        _ => fit_cell(&plain, false),
+    };
+    match config.cell_width() {
+        3 => cell,
+        width => narrow(&cell, width),
+    }
+}
+
+/// narrow
+/// The middle `width` columns of a 3 wide painted cell, the left two for
+/// a width of 2, keeping its colors. A character cut in half is left out
+/// for spaces.
+fn narrow(cell: &str, width: usize) -> String {
+    let (start, end) = ((3 - width) / 2, (3 - width) / 2 + width);
+    let mut out = String::new();
+    let mut col = 0;
+    let mut rest = cell;
+    while let Some(c) = rest.chars().next() {
+        if let Some(seq) = rest.strip_prefix("\x1b[") {
+            let end = seq.find(|c: char| !(c.is_ascii_digit() || c == ';')).map_or(seq.len(), |end| end + 1);
+            out.push_str(&rest[..2 + end]);
+            rest = &seq[end..];
+            continue;
+        }
+        let w = c.width().unwrap_or(0);
+        if col >= start && col + w <= end {
+            out.push(c);
+        } else if col < end && col + w > start {
+            out.push_str(&" ".repeat((col + w).min(end) - col.max(start)));
+        }
+        col += w;
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// line_glyph
+/// The text drawn at board cell `pos`, which with half blocks also covers
+/// the cell below it, see `half_block`.
+fn line_glyph(state: &GameState, config: &RenderConfig, pos: Position) -> String {
+    if !config.half_blocks {
+        return cell_glyph(state, config, pos);
+    }
+    let below = Position::new(pos.x(), pos.y() + 1);
+    half_block(state, config, pos, (below.y() < state.height()).then_some(below))
+}
+
+/// Half
+/// What half a half block cell shows.
+enum Half {
+    Open,
+    Wall,
+    /// Anything else, drawn as its usual cell.
+    Marked(String),
+}
+
+/// half_block
+/// Board cells `top` and `bottom` packed into one cell of half blocks,
+/// e.g. `▀` for a wall over an open cell. Walls and open cells have a half
+/// each, but a cell holding anything else is drawn whole, hiding the other
+/// one, the top's first. No `bottom`, past the end of the board, is open.
+fn half_block(state: &GameState, config: &RenderConfig, top: Position, bottom: Option<Position>) -> String {
+    let half = |pos: Position| {
+        let cell = cell_glyph(state, config, pos);
+        if cell.trim().is_empty() {
+            Half::Open
+        } else if state.board[pos.y()][pos.x()] == WALL {
+            Half::Wall
+        } else {
+            Half::Marked(cell)
+        }
+    };
+    let block = match (half(top), bottom.map_or(Half::Open, half)) {
+        (Half::Marked(cell), _) | (_, Half::Marked(cell)) => return cell,
+        (Half::Open, Half::Open) => ' ',
+        (Half::Wall, Half::Open) => '▀',
+        (Half::Open, Half::Wall) => '▄',
+        (Half::Wall, Half::Wall) => '█',
+    };
+    let theme = &config.theme;
+    theme.paint(theme.wall, &block.to_string().repeat(config.cell_width()))
+}
+
+/// styled_glyph
+/// The text for board cell `pos`, meant to be 3 columns wide.
+fn styled_glyph(state: &GameState, config: &RenderConfig, pos: Position) -> String {
+    let theme = &config.theme;
+    let v = state.board[pos.y()][pos.x()];
+    if !state.is_visible(pos) {
+        return "   ".to_string();
+    }
+    if v != SYMBOL && state.enemies.contains(&pos) {
+        return theme.paint(theme.enemy, &format!(" {ENEMY} "));
+    }
+    if let Some(rival) = state.rival.filter(|rival| v != SYMBOL && rival.position == pos) {
+        return match config.color {
+            true => theme.paint(theme.rival, player_sprite(rival.facing)),
+            false => PLAIN_RIVAL.to_string(),
+        };
+    }
+    if state.hint.is_some_and(|(hint, _)| hint == pos) {
+        let glyph = match config.glyphs.goal_cell(config.cell_width()) {
+            Some(cell) if v == GOAL && !config.color => cell,
+            None if v == GOAL && !config.color => PLAIN_GOAL.to_string(),
+            _ => format!(" {HINT} "),
+        };
+        return theme.paint(theme.hint, &glyph);
+    }
+    let heat = state.heat_map.as_ref().and_then(|heat| heat.level(pos, theme.heat.len()));
+    match v {
+        SYMBOL => match (config.glyphs.player_cell(config.cell_width()), config.color) {
+            (Some(cell), _) => theme.paint(theme.player, &cell),
+            (None, false) => PLAIN_PLAYER.to_string(),
+            (None, true) => theme.paint(theme.player, player_sprite(state.facing)),
+        },
+        GOAL => match (config.glyphs.goal_cell(config.cell_width()), config.color) {
+            (Some(cell), _) => theme.paint(theme.goal, &cell),
+            (None, false) => PLAIN_GOAL.to_string(),
+            (None, true) => theme.paint(theme.goal, &format!("{v}{v}{v}")),
+        },
+        WALL if config.walls == WallStyle::Lines => theme.paint(theme.wall, &wall_lines(&state.board, pos)),
+        WALL => theme.paint(theme.wall, &format!("{v}{v}{v}")),
+        ' ' if state.items.contains_key(&pos) => theme.paint(theme.item, &format!(" {ITEM} ")),
+        ' ' if state.portals.contains_key(&pos) => theme.paint(theme.portal, &format!(" {PORTAL} ")),
+        ' ' if state.one_way.contains_key(&pos) => {
+            theme.paint(theme.gate, &format!(" {} ", gate_arrow(state.one_way[&pos])))
+        }
+        ' ' if state.ice.contains(&pos) => theme.paint(theme.ice, &format!("{ICE}{ICE}{ICE}")),
+        ' ' if state.collected_goals.contains(&pos) => {
+            let glyph = config.glyphs.collected_goal_cell(config.cell_width()).unwrap_or(format!(" {GOAL} "));
+            theme.paint(&Theme::with("2", theme.goal), &glyph)
+        }
+        ' ' if state.exploration.as_ref().is_some_and(|e| e.frontier.contains(&pos)) => {
+            theme.paint(theme.frontier, &format!(" {FRONTIER} "))
+        }
+        ' ' if state.solution_path.as_ref().is_some_and(|path| path.contains(&pos)) => {
+            theme.paint(&Theme::with("2", theme.hint), &format!(" {SOLUTION} "))
+        }
+        ' ' if state.exploration.as_ref().is_some_and(|e| e.expanded.contains(&pos)) => {
+            theme.paint(theme.explored, &format!(" {EXPLORED} "))
+        }
+        ' ' if heat.is_some() => theme.paint(&format!("48;5;{}", theme.heat[heat.unwrap_or(0)]), "   "),
+        ' ' if state.hardcore && state.visited.contains(&pos) => theme.paint(theme.trail, "▒▒▒"),
+        ' ' if state.costs.contains_key(&pos) => {
+            let cost = state.costs[&pos];
+            let shade = theme.mud[(cost as usize).saturating_sub(2).min(theme.mud.len() - 1)];
+            theme.paint(&format!("30;48;5;{shade}"), &format!(" {cost} "))
+        }
+        ' ' if state.show_trail && state.visited.contains(&pos) => theme.paint(theme.trail, &format!(" {TRAIL} ")),
+        _ => format!("{v}{v}{v}"),
+    }
+}
+
+/// gate_arrow
+/// Points the way a one-way gate can be crossed.
+fn gate_arrow(dir: Movement) -> char {
+    match dir {
+        Movement::UP => '↑',
+        Movement::DOWN => '↓',
+        Movement::LEFT => '←',
+        Movement::RIGHT => '→',
+        Movement::UP_LEFT => '↖',
+        Movement::UP_RIGHT => '↗',
+        Movement::DOWN_LEFT => '↙',
+        Movement::DOWN_RIGHT => '↘',
+    }
+}
+
+/// Screen
+/// Where a frame's view sits on the terminal. Rows and columns are 1 based
+/// screen coordinates of the top left border corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Screen {
+    dims: (usize, usize),
+    view: Viewport,
+    col: usize,
+    row: usize,
+}
+
+impl Screen {
+    fn new(state: &GameState, config: &RenderConfig, dims: (usize, usize)) -> Screen {
+        let view = Viewport::centered_with(state, dims, config.cell_width(), config.rows_per_line());
+        let (col, row) = view.origin(dims);
+        Screen { dims, view, col: col.max(1), row: row.max(1) }
+    }
+
+    /// Cursor move to the left edge of board cell `pos`.
+    fn goto(&self, pos: Position) -> String {
+        let row = self.row + 1 + (pos.y() - self.view.top) / self.view.rows_per_line;
+        let col = self.col + 1 + self.view.cell_width * (pos.x() - self.view.left);
+        format!("{ESC}[{row};{col}H")
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Position> + '_ {
+        (self.view.top..self.view.top + self.view.height).step_by(self.view.rows_per_line)
+            .flat_map(|y| (self.view.left..self.view.left + self.view.width).map(move |x| Position::new(x, y)))
+    }
+}
+
+/// render_header
+/// The header centered on the line above the top border, cut to the
+/// terminal's width. Left off when the border is on the first line.
+fn render_header(frame: &mut String, config: &RenderConfig, screen: &Screen) {
+    if let Some(header) = config.header.as_ref().filter(|_| screen.row > 1) {
+        let header = take_width(header, screen.dims.0);
+        let x = centered(header.width(), screen.dims.0);
+        frame.push_str(format!("{ESC}[{row};{x}H{header}", row = screen.row - 1).as_str());
+    }
+}
+
+/// draw_top_border
+/// The top border with the countdown set into its middle.
+fn draw_top_border(frame: &mut String, state: &GameState, config: &RenderConfig, screen: &Screen) {
+    let theme = &config.theme;
+    let [top_left, top_right, _, _, across, _] = border_glyphs(config.walls);
+    let border_len = screen.view.cell_width * screen.view.width + 2;
+    frame.push_str(format!("{ESC}[{y};{x}H", y=screen.row, x=screen.col).as_str());
+    for i in 0..border_len {
+        if i == border_len/2 - 1 {
+            frame.push_str(&theme.paint(theme.timer, &format!("{:03}", state.time_remaining)));
+        } else if i == border_len/2 || i == border_len/2 + 1 {
+            // Do Nothing
+        } else {
+            let c = match i {
+                0 => top_left,
+                _ if i == border_len - 1 => top_right,
+                _ => across,
+            };
+            frame.push_str(&theme.paint(theme.border, &c.to_string()));
+        }
+    }
+}
+
+/// border_glyphs
+/// The top left, top right, bottom left and bottom right corners of the
+/// border, then its horizontal and vertical edges.
+fn border_glyphs(walls: WallStyle) -> [char; 6] {
+    match walls {
+        WallStyle::Block => [BORDER; 6],
+        WallStyle::Lines => ['┌', '┐', '└', '┘', '─', '│'],
+    }
+}
+
+/// draw_status
+/// The status line below the board.
+fn draw_status(frame: &mut String, state: &GameState, screen: &Screen) {
+    let row = screen.row + screen.view.lines() + 2;
+    frame.push_str(format!("{ESC}[{row};1H{ESC}[2K{ESC}[{x}G", x=screen.col).as_str());
+    frame.push_str(&status_line(state));
+}
+
+/// status_line
+/// Moves, time, score and any message.
+fn status_line(state: &GameState) -> String {
+    let secs = state.elapsed().as_secs();
+    let mut line = String::new();
+    line.push_str(format!("Moves: {}  Time: {:02}:{:02}", state.moves, secs / 60, secs % 60).as_str());
+    line.push_str(format!("  Score: {}", state.score).as_str());
+    if let Some(rival) = state.rival {
+        line.push_str(format!("  P2 Moves: {}", rival.moves).as_str());
+    }
+    let total_goals = state.goals.len() + state.collected_goals.len();
+    if total_goals > 1 {
+        line.push_str(format!("  Goals: {}/{total_goals}", state.collected_goals.len()).as_str());
+    }
+    if let Some(par) = state.par {
+        match state.moves.checked_sub(par).filter(|&over| over > 0) {
+            Some(over) => line.push_str(format!("  Par: {par} (failed, {over} over)").as_str()),
+            None => line.push_str(format!("  Par: {par} ({} left)", par - state.moves).as_str()),
+        }
+    }
+    if let Some((radius, secs)) = state.visibility_radius.zip(state.fog_closes_in()) {
+        match radius {
+            1 => line.push_str(format!("  Fog: {radius}, blind in {secs}s!").as_str()),
+            _ => line.push_str(format!("  Fog: {radius}, closing in {secs}s").as_str()),
+        }
+    }
+    if let Some(MazeStats { path_length: Some(path), dead_ends }) = state.stats {
+        line.push_str(format!("  Difficulty: path={path}, dead-ends={dead_ends}").as_str());
+    }
+    if let Some(message) = &state.message {
+        line.push_str(format!("  {message}").as_str());
+    }
+    line
+}
+
+/// draw_legend
+/// The controls legend centered on the line below the status, left off
+/// when it would fall past the bottom of the terminal.
+fn draw_legend(frame: &mut String, config: &RenderConfig, screen: &Screen) {
+    let row = screen.row + screen.view.lines() + 3;
+    if let Some(legend) = config.legend.as_ref().filter(|_| row <= screen.dims.1) {
+        let legend = take_width(legend, screen.dims.0);
+        let x = centered(legend.width(), screen.dims.0);
+        frame.push_str(format!("{ESC}[{row};{x}H{legend}").as_str());
+    }
+}
+
+/// draw_overlays
+/// The minimap, when the board doesn't fit on screen, and the end of game
+/// or pause banner.
+fn draw_overlays(frame: &mut String, state: &GameState, config: &RenderConfig, screen: &Screen) {
+    let view = screen.view;
+    if (view.width, view.height) != (state.width(), state.height()) {
+        frame.push_str(&render_minimap(state, &config.theme, screen.dims));
+    }
+
+    if state.victory || state.failure {
+        let message = match state.winner {
+            _ if state.failure => " You Lose!",
+            Some(0) => "P1 Wins!",
+            Some(_) => "P2 Wins!",
+            None => config.win_text.as_deref().unwrap_or("You Did It!"),
+        };
+        let summary = match state.par {
+            _ if state.winner == Some(1) => format!("in {} moves", state.rival.map_or(0, |r| r.moves)),
+            Some(par) if state.over_par() => format!("in {} moves, {} over par", state.moves, state.moves - par),
+            Some(_) => format!("in {} moves, under par", state.moves),
+            None => format!("in {} moves", state.moves),
+        };
+        let subtitle = state.victory.then_some(summary.as_str());
+        draw_banner(frame, message, subtitle, config.font.as_deref(), screen.dims);
+    } else if state.paused {
+        draw_banner(frame, "PAUSED", Some("space: resume  q: quit"), config.font.as_deref(), screen.dims);
+    } else if let Some(secs) = state.countdown() {
+        let message = if secs == 0 { "GO!".to_string() } else { secs.to_string() };
+        draw_banner(frame, &message, None, config.font.as_deref(), screen.dims);
+    }
+}
+
+/// has_banner
+fn has_banner(state: &GameState) -> bool {
+    state.victory || state.failure || state.paused || state.countdown().is_some()
+}
+
+/// push_frame
+/// Clears the screen and draws the whole of `state`.
+fn push_frame(frame: &mut String, state: &GameState, config: &RenderConfig, screen: &Screen) {
+    let theme = &config.theme;
+    let [_, _, bottom_left, bottom_right, across, side] = border_glyphs(config.walls);
+    let border = theme.paint(theme.border, &side.to_string());
+    frame.push_str(format!("{fill}{ESC}[2J", fill=theme.fill()).as_str());
+    render_header(frame, config, screen);
+    draw_top_border(frame, state, config, screen);
+
+    // Draw each visible row
+    for (i, y) in (screen.view.top..screen.view.top + screen.view.height).step_by(screen.view.rows_per_line).enumerate() {
+        frame.push_str(format!("{ESC}[{row};{x}H", row=screen.row + 1 + i, x=screen.col).as_str());
+        frame.push_str(&border);
+        for x in screen.view.left..screen.view.left + screen.view.width {
+            frame.push_str(&line_glyph(state, config, Position::new(x, y)));
+        }
+        frame.push_str(&border);
+    }
+
+    // Draw Bottom Border
+    frame.push_str(format!("{ESC}[{row};{x}H", row=screen.row + screen.view.lines() + 1, x=screen.col).as_str());
+    frame.push_str(&theme.paint(theme.border, &bottom_left.to_string()));
+    for _ in 0..screen.view.cell_width * screen.view.width {
+        frame.push_str(&theme.paint(theme.border, &across.to_string()));
+    }
+    frame.push_str(&theme.paint(theme.border, &bottom_right.to_string()));
+
+    draw_status(frame, state, screen);
+    if !has_banner(state) {
+        draw_legend(frame, config, screen);
+    }
+    draw_overlays(frame, state, config, screen);
+}
+
+/// push_changes
+/// Redraws only the cells that look different from `prev`, plus the
+/// countdown, status line and overlays. `prev` must have been drawn on the
+/// same `screen`.
+fn push_changes(frame: &mut String, prev: &GameState, state: &GameState, config: &RenderConfig, screen: &Screen) {
+    frame.push_str(&config.theme.fill());
+    if prev.time_remaining != state.time_remaining {
+        draw_top_border(frame, state, config, screen);
+    }
+    for pos in screen.cells() {
+        let glyph = line_glyph(state, config, pos);
+        if glyph != line_glyph(prev, config, pos) {
+            frame.push_str(&screen.goto(pos));
+            frame.push_str(&glyph);
+        }
+    }
+    draw_status(frame, state, screen);
+    draw_overlays(frame, state, config, screen);
+}
+
+/// write_frame
+/// Writes `frame` in a single call, stripping its colors first if the
+/// config has them turned off.
+fn write_frame(w: &mut impl Write, frame: String, config: &RenderConfig) -> io::Result<()> {
+    let frame = if config.color { frame } else { strip_colors(&frame) };
+    w.write_all(frame.as_bytes())
+}
+
+/// ring
+/// Rings the bell once for a move blocked since `prev`, or a few times if
+/// the game was just won.
+fn ring(w: &mut impl Write, prev: &GameState, state: &GameState) -> io::Result<()> {
+    if state.victory && !prev.victory {
+        for i in 0..VICTORY_BELLS {
+            if i > 0 {
+                thread::sleep(VICTORY_BELL_GAP);
+            }
+            w.write_all(BELL.as_bytes())?;
+            w.flush()?;
+        }
+    } else if state.bumps > prev.bumps {
+        w.write_all(BELL.as_bytes())?;
+        w.flush()?;
+    }
+    Ok(())
+}
+
+/// draw_frame
+/// Writes the escape sequences that draw the whole of `state` on a `dims`
+/// sized terminal to `w`, using the default config.
+pub fn draw_frame(w: &mut impl Write, state: &GameState, dims: (usize, usize)) -> io::Result<()> {
+    draw_frame_with(w, state, &RenderConfig::default(), dims)
+}
+
+/// draw_frame_with
+/// Writes the escape sequences that draw the whole of `state` on a `dims`
+/// sized terminal to `w`, using `config`.
+pub fn draw_frame_with(w: &mut impl Write, state: &GameState, config: &RenderConfig, dims: (usize, usize)) -> io::Result<()> {
+    let mut frame = String::new();
+    push_frame(&mut frame, state, config, &Screen::new(state, config, dims));
+    write_frame(w, frame, config)
+}
+
+/// start_render
+/// Draws every state received on `rx`. The terminal size is read for each
+/// frame, so the board re-centers when the window is resized. The last
+/// state drawn is kept so that, while the view stays put, only the cells
+/// that changed are redrawn instead of clearing the whole screen. Drawing
+/// stops once every sender is gone.
+pub fn start_render(rx: mpsc::Receiver<GameState>, config: RenderConfig) -> Renderer {
+    Renderer(thread::spawn(move || {
+        let mut config = config;
+        let mut last: Option<(GameState, Screen)> = None;
+        while let Ok(state) = rx.recv() {
+            config.zoom = state.zoom;
+            let dims = term_size::dimensions()
+                .unwrap_or((config.cell_width() * state.width() + 2, state.height().div_ceil(config.rows_per_line()) + 4));
+            let screen = Screen::new(&state, &config, dims);
+            let mut out = io::stdout().lock();
+            let written = match &last {
+                Some((prev, prev_screen)) if *prev_screen == screen && !has_banner(prev) && !has_banner(&state) => {
+                    let mut frame = String::new();
+                    push_changes(&mut frame, prev, &state, &config, &screen);
+                    write_frame(&mut out, frame, &config)
+                }
+                _ => draw_frame_with(&mut out, &state, &config, dims),
+            };
+            // A closed stdout ends the thread.
+            if written.and_then(|_| out.flush()).is_err() {
+                return;
+            }
+            if let Some((prev, _)) = last.as_ref().filter(|_| config.sound) {
+                if ring(&mut out, prev, &state).is_err() {
+                    return;
                 }
-                print!("{frame}");
-                io::stdout().flush().unwrap();
             }
+            last = Some((state, screen));
         }
-    });
+    }))
 }
 
+/// Renderer
+/// The thread drawing the game, see `start_render`.
+pub struct Renderer(thread::JoinHandle<()>);
+
+impl Renderer {
+    /// is_running
+    pub fn is_running(&self) -> bool {
+        !self.0.is_finished()
+    }
+
+    /// crash
+    /// What the renderer panicked with, if it stopped that way. Doesn't
+    /// wait for a renderer that's still running.
+    pub fn crash(self) -> Option<String> {
+        if self.is_running() {
+            return None;
+        }
+        self.finish()
+    }
+
+    /// finish
+    /// Waits for the renderer to stop, then what it panicked with if it
+    /// stopped that way.
+    pub fn finish(self) -> Option<String> {
+        let payload = self.0.join().err()?;
+        Some(match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+            (Some(msg), _) => msg.to_string(),
+            (None, Some(msg)) => msg.clone(),
+            (None, None) => "unknown error".to_string(),
+        })
+    }
+}
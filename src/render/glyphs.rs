@@ -0,0 +1,146 @@
+///
+/// Glyphs Module
+///
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::game_state::{Position, WALL};
+
+/// Glyphs
+/// Text drawn in place of the usual player and goal, e.g. emoji or plain
+/// ASCII. Every cell is 3 columns wide, so each glyph is fitted to that
+/// by its display width, see `fit_cell`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Replaces the arrow showing which way the player faces.
+    pub player: Option<String>,
+    /// Replaces the goal's `▓▓▓`.
+    pub goal: Option<String>,
+}
+
+impl Glyphs {
+    /// player_cell
+    /// The player glyph fitted to a 3 wide cell. None if there isn't one,
+    /// or if cells are `cell_width` 1 and it's any wider.
+    pub fn player_cell(&self, cell_width: usize) -> Option<String> {
+        fits(&self.player, cell_width).map(|glyph| fit_cell(glyph, false))
+    }
+
+    /// goal_cell
+    /// Like `player_cell`, repeating a one column goal to fill the cell.
+    pub fn goal_cell(&self, cell_width: usize) -> Option<String> {
+        fits(&self.goal, cell_width).map(|glyph| fit_cell(glyph, true))
+    }
+
+    /// collected_goal_cell
+    /// Like `goal_cell`, drawn once in the middle of the cell.
+    pub fn collected_goal_cell(&self, cell_width: usize) -> Option<String> {
+        fits(&self.goal, cell_width).map(|glyph| fit_cell(glyph, false))
+    }
+}
+
+/// fits
+/// `glyph` if it fits a `cell_width` wide cell.
+fn fits(glyph: &Option<String>, cell_width: usize) -> Option<&str> {
+    glyph.as_deref().filter(|glyph| cell_width > 1 || glyph.width() == 1)
+}
+
+/// WallStyle
+/// How walls are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WallStyle {
+    /// Every wall cell filled with `░`.
+    #[default]
+    Block,
+    /// Thin box drawing lines joining each wall to its neighbors.
+    Lines,
+}
+
+impl fmt::Display for WallStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            WallStyle::Block => "block",
+            WallStyle::Lines => "lines",
+        })
+    }
+}
+
+impl FromStr for WallStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WallStyle, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "block" => Ok(WallStyle::Block),
+            "lines" => Ok(WallStyle::Lines),
+            _ => Err(format!("unknown wall style {s:?}, expected block or lines")),
+        }
+    }
+}
+
+/// wall_lines
+/// The 3 wide box drawing cell for the wall at `pos`, with a line out to
+/// each side that has a wall next to it. Off the board counts as wall, so
+/// walls at the edge run into the border.
+pub fn wall_lines(board: &[Vec<char>], pos: Position) -> String {
+    let (x, y) = (pos.x(), pos.y());
+    let wall = |x: Option<usize>, y: Option<usize>| match (x, y) {
+        (Some(x), Some(y)) => board.get(y).and_then(|row| row.get(x)).is_none_or(|&c| c == WALL),
+        _ => true,
+    };
+    let up = wall(Some(x), y.checked_sub(1));
+    let down = wall(Some(x), Some(y + 1));
+    let left = wall(x.checked_sub(1), Some(y));
+    let right = wall(Some(x + 1), Some(y));
+    let junction = match (up, down, left, right) {
+        (false, false, false, false) => '■',
+        (_, _, false, false) => '│',
+        (false, false, _, _) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    };
+    let arm = |joined: bool| if joined { '─' } else { ' ' };
+    [arm(left), junction, arm(right)].iter().collect()
+}
+
+/// fit_cell
+/// `glyph` made exactly 3 columns wide. A single column glyph is centered,
+/// or repeated to fill the cell like the usual goal if `repeat`. A 2 column
+/// one (most emoji) gets a trailing space, and anything wider is cut short.
+pub fn fit_cell(glyph: &str, repeat: bool) -> String {
+    match glyph.width() {
+        1 if repeat => glyph.repeat(3),
+        1 => format!(" {glyph} "),
+        3 => glyph.to_string(),
+        _ => {
+            let cell = take_width(glyph, 3);
+            let width = cell.width();
+            cell + &" ".repeat(3 - width)
+        }
+    }
+}
+
+/// take_width
+/// As much of the start of `text` as fits in `cols` terminal columns,
+/// never splitting a wide character.
+pub fn take_width(text: &str, cols: usize) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w > cols {
+            break;
+        }
+        out.push(c);
+        width += w;
+    }
+    out
+}
@@ -0,0 +1,64 @@
+///
+/// PNG Export Module
+///
+use std::path::Path;
+
+use image::{ImageResult, Rgb, RgbImage};
+
+use crate::game_state::{GOAL, SYMBOL, WALL};
+
+/// PngStyle
+/// Cell size in pixels and the colors used for each kind of cell.
+#[derive(Debug, Clone, Copy)]
+pub struct PngStyle {
+    pub cell_px: u32,
+    pub wall: [u8; 3],
+    pub floor: [u8; 3],
+    pub player: [u8; 3],
+    pub goal: [u8; 3],
+}
+
+impl Default for PngStyle {
+    fn default() -> PngStyle {
+        PngStyle {
+            cell_px: 8,
+            wall: [40, 40, 40],
+            floor: [240, 240, 240],
+            player: [30, 120, 220],
+            goal: [190, 40, 190],
+        }
+    }
+}
+
+/// export_png
+/// Draws the board with a one cell border to `path` using the default colors.
+pub fn export_png(board: &[Vec<char>], path: &Path, cell_px: u32) -> ImageResult<()> {
+    export_png_with(board, path, &PngStyle { cell_px, ..PngStyle::default() })
+}
+
+/// export_png_with
+/// Draws the board with a one cell border to `path` using `style`.
+pub fn export_png_with(board: &[Vec<char>], path: &Path, style: &PngStyle) -> ImageResult<()> {
+    let width = board.first().map(|row| row.len()).unwrap_or(0) as u32 + 2;
+    let height = board.len() as u32 + 2;
+    let px = style.cell_px.max(1);
+
+    let mut img = RgbImage::from_pixel(width * px, height * px, Rgb(style.wall));
+    for (y, row) in board.iter().enumerate() {
+        for (x, v) in row.iter().enumerate() {
+            let color = match *v {
+                WALL => continue,
+                SYMBOL => style.player,
+                GOAL => style.goal,
+                _ => style.floor,
+            };
+            let (cx, cy) = ((x as u32 + 1) * px, (y as u32 + 1) * px);
+            for dy in 0..px {
+                for dx in 0..px {
+                    img.put_pixel(cx + dx, cy + dy, Rgb(color));
+                }
+            }
+        }
+    }
+    img.save(path)
+}
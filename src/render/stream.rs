@@ -0,0 +1,40 @@
+///
+/// Frame Stream Module
+///
+/// Games streamed to spectators, one frame per line as JSON:
+///
+/// ```text
+/// {"frame":"░░░░░░░░░░░\n░◀◆▶░░░▓▓▓░\n░         ░\n░░░░░░░░░░░\n","status":"Moves: 0  Time: 00:00  Score: 0  Difficulty: path=4, dead-ends=0"}
+/// ```
+///
+/// `frame` is the maze as `export_ascii` draws it and `status` the line
+/// shown under it.
+use serde::{Deserialize, Serialize};
+
+use super::{export_ascii, status_line};
+use crate::game_state::GameState;
+
+/// Record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub frame: String,
+    pub status: String,
+}
+
+impl Record {
+    /// of
+    pub fn of(state: &GameState) -> Record {
+        Record { frame: export_ascii(state.board()), status: status_line(state) }
+    }
+
+    /// to_line
+    /// The record as a line of JSON, without the newline.
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// parse
+    pub fn parse(line: &str) -> Result<Record, String> {
+        serde_json::from_str(line).map_err(|e| e.to_string())
+    }
+}
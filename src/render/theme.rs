@@ -0,0 +1,164 @@
+///
+/// Color Theme Module
+///
+use std::fmt;
+use std::str::FromStr;
+
+/// Blue through green and yellow to red.
+const RAINBOW: &[u8] = &[21, 27, 33, 39, 45, 51, 50, 49, 48, 47, 46, 82, 118, 154, 190, 226, 220, 214, 208, 202, 196];
+
+/// Theme
+/// SGR parameters (the `..` in `ESC [ .. m`) for each part of the screen.
+/// An empty string leaves that part in the terminal's default style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub wall: &'static str,
+    pub border: &'static str,
+    pub player: &'static str,
+    pub goal: &'static str,
+    pub trail: &'static str,
+    pub hint: &'static str,
+    pub item: &'static str,
+    pub enemy: &'static str,
+    pub portal: &'static str,
+    pub gate: &'static str,
+    pub ice: &'static str,
+    /// The second player in a race.
+    pub rival: &'static str,
+    /// Cells a visualized search has expanded, and found but not expanded.
+    pub explored: &'static str,
+    pub frontier: &'static str,
+    pub timer: &'static str,
+    pub background: &'static str,
+    /// 256 color palette indexes for the heat map, nearest first.
+    pub heat: &'static [u8],
+    /// 256 color palette backgrounds for mud, cheapest first.
+    pub mud: &'static [u8],
+}
+
+impl Theme {
+    /// The original look, magenta goals on the terminal's own colors.
+    pub const DEFAULT: Theme = Theme {
+        name: "default",
+        wall: "",
+        border: "",
+        player: "",
+        goal: "35",
+        trail: "2",
+        hint: "5;1;32",
+        item: "1;33",
+        enemy: "1;31",
+        portal: "1;36",
+        gate: "1;34",
+        ice: "96",
+        rival: "1;32",
+        explored: "34",
+        frontier: "1;33",
+        timer: "4;35",
+        background: "",
+        heat: RAINBOW,
+        mud: &[180, 137, 94],
+    };
+
+    /// Bright colors on black for hard to read terminals.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        name: "high-contrast",
+        wall: "97",
+        border: "97",
+        player: "1;93",
+        goal: "1;95",
+        trail: "96",
+        hint: "5;1;92",
+        item: "1;92",
+        enemy: "1;91",
+        portal: "1;96",
+        gate: "1;94",
+        ice: "1;96",
+        rival: "1;92",
+        explored: "94",
+        frontier: "1;93",
+        timer: "1;4;97",
+        background: "40",
+        heat: RAINBOW,
+        mud: &[180, 137, 94],
+    };
+
+    /// No colors, parts are told apart by bold, dim and reverse video.
+    pub const MONOCHROME: Theme = Theme {
+        name: "monochrome",
+        wall: "",
+        border: "",
+        player: "1",
+        goal: "7",
+        trail: "2",
+        hint: "5;7",
+        item: "1",
+        enemy: "1;7",
+        portal: "1;4",
+        gate: "1",
+        ice: "2",
+        rival: "4",
+        explored: "2",
+        frontier: "1",
+        timer: "4",
+        background: "",
+        heat: &[236, 238, 240, 242, 244, 246, 248, 250, 252, 254],
+        mud: &[250, 245, 240],
+    };
+
+    /// Every built in theme.
+    pub const ALL: [Theme; 3] = [Theme::DEFAULT, Theme::HIGH_CONTRAST, Theme::MONOCHROME];
+
+    /// paint
+    /// `text` drawn with the SGR parameters `code` over the background,
+    /// ending back on the plain background.
+    pub fn paint(&self, code: &str, text: &str) -> String {
+        match (code, self.background) {
+            ("", "") => text.to_string(),
+            (code, "") => format!("\x1b[{code}m{text}\x1b[0m"),
+            ("", bg) => format!("{text}\x1b[0;{bg}m"),
+            (code, bg) => format!("\x1b[0;{bg};{code}m{text}\x1b[0;{bg}m"),
+        }
+    }
+
+    /// with
+    /// `code` with the extra SGR parameters `extra` added in front.
+    pub fn with(extra: &str, code: &str) -> String {
+        match code {
+            "" => extra.to_string(),
+            code => format!("{extra};{code}"),
+        }
+    }
+
+    /// fill
+    /// The sequence that sets the background, empty for the default theme.
+    pub fn fill(&self) -> String {
+        match self.background {
+            "" => String::new(),
+            bg => format!("\x1b[{bg}m"),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::DEFAULT
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Theme, String> {
+        Theme::ALL.into_iter()
+            .find(|t| t.name.eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown theme {s:?}, expected default, high-contrast or monochrome"))
+    }
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "serde")]
+
+use mazeme::config::Config;
+use mazeme::render::Theme;
+use mazeme::{Difficulty, KeyBindings, MazeAlgorithm};
+
+#[test]
+fn config_files_fill_in_what_they_name() {
+    let config = Config::parse(
+        "width = 41\nalgo = \"kruskal\"\ndifficulty = \"hard\"\ntheme = \"high-contrast\"\nquiet = true\n\n[keys]\nup = \"k\"\ndown = \"j\"\n",
+    ).unwrap();
+    assert_eq!(config.size(), Some((41, 41)));
+    assert_eq!(config.algo, Some(MazeAlgorithm::Kruskal));
+    assert_eq!(config.difficulty, Some(Difficulty::Hard));
+    assert_eq!(config.theme, Some(Theme::HIGH_CONTRAST));
+    assert!(config.quiet && !config.no_color);
+    assert_eq!(config.keys, Some(KeyBindings { up: 'k', down: 'j', ..KeyBindings::default() }));
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+}
+
+#[test]
+fn malformed_configs_are_rejected() {
+    assert!(Config::parse("algo = \"bogo\"").unwrap_err().contains("bogo"));
+    assert!(Config::parse("braid = 2.0").is_err());
+    assert!(Config::parse("sizes = 3").is_err());
+    assert!(Config::parse("[keys]\nup = \"wk\"").is_err());
+    assert!(Config::parse("[keys]\ndown = \"o\"").is_err());
+    assert!(Config::parse("width = ").is_err());
+}
+
+#[test]
+fn missing_config_files_are_the_defaults() {
+    let path = std::env::temp_dir().join(format!("mazeme_no_config_{}.toml", std::process::id()));
+    assert_eq!(Config::load(&path), Ok(Config::default()));
+}
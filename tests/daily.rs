@@ -0,0 +1,15 @@
+use mazeme::daily::Date;
+
+#[test]
+fn days_since_the_epoch_become_dates() {
+    assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+    assert_eq!(Date::from_days(11016).to_string(), "2000-02-29");
+    assert_eq!(Date::from_days(20088).to_string(), "2024-12-31");
+    assert_eq!(Date::from_days(20089).to_string(), "2025-01-01");
+}
+
+#[test]
+fn daily_seeds_read_as_the_date() {
+    assert_eq!(Date::from_days(20741).seed(), 20261015);
+    assert!((19700101..=99991231).contains(&Date::today().seed()));
+}
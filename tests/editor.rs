@@ -0,0 +1,45 @@
+use console::Key;
+
+use mazeme::editor::{Editor, EditorAction};
+use mazeme::game_state::{GOAL, SYMBOL, WALL};
+use mazeme::render::import_ascii;
+use mazeme::Position;
+
+#[test]
+fn designs_play_once_they_have_a_route() {
+    let mut editor = Editor::new(3, 1);
+    for key in [Key::Char('s'), Key::ArrowRight, Key::Char(' '), Key::ArrowRight, Key::Char('g')] {
+        editor.handle_key(&key);
+    }
+    assert_eq!(editor.board()[0], vec![SYMBOL, WALL, GOAL]);
+    assert!(matches!(editor.handle_key(&Key::Enter), EditorAction::Continue));
+    assert!(editor.message.as_ref().is_some_and(|m| m.contains("can't be reached")));
+
+    editor.handle_key(&Key::ArrowLeft);
+    editor.handle_key(&Key::Char(' '));
+    match editor.handle_key(&Key::Enter) {
+        EditorAction::Play(state) => {
+            assert_eq!((state.position, state.win_position), (Position::new(0, 0), Position::new(2, 0)));
+        }
+        _ => panic!("expected to play"),
+    }
+}
+
+#[test]
+fn start_and_goal_move_and_saves_load_back() {
+    let mut editor = Editor::new(3, 3);
+    editor.place(SYMBOL);
+    editor.move_cursor(mazeme::Movement::DOWN);
+    editor.place(SYMBOL);
+    editor.place(GOAL);
+    assert_eq!(editor.board().iter().flatten().filter(|&&c| c == SYMBOL).count(), 0);
+    for _ in 0..5 {
+        editor.move_cursor(mazeme::Movement::RIGHT);
+    }
+    assert_eq!(editor.cursor(), Position::new(2, 1));
+    editor.place(SYMBOL);
+    let EditorAction::Save(text) = editor.handle_key(&Key::Char('o')) else {
+        panic!("expected to save");
+    };
+    assert_eq!(Editor::from_state(&import_ascii(&text).unwrap()).board(), editor.board());
+}
@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use mazeme::game_state::{convert_generated_maze, GOAL, SYMBOL, WALL};
+use mazeme::game_state::gen::generate_maze_steps;
+use mazeme::game_state::solve::{exploration_frames, explore, SearchAlgorithm};
+use mazeme::game_state::stats::HeatMap;
+use mazeme::{
+    analyze, braid, euclidean, generate_maze_with, is_solvable, manhattan, render_dims, solve, solve_astar, solve_dfs, GameSetup,
+    GameState, MazeAlgorithm, MazeStats, Position,
+};
+
+const ALGORITHMS: [MazeAlgorithm; 6] = [
+    MazeAlgorithm::RecursiveBacktracker,
+    MazeAlgorithm::Prim,
+    MazeAlgorithm::Kruskal,
+    MazeAlgorithm::Wilson,
+    MazeAlgorithm::Eller,
+    MazeAlgorithm::RecursiveDivision,
+];
+
+#[test]
+fn seeded_mazes_are_solvable_corner_to_corner() {
+    for algo in ALGORITHMS {
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let size = 5 + (seed as usize % 20) * 2;
+            let board = convert_generated_maze(generate_maze_with(algo, size, size, &mut rng));
+            let goal = Position::new(board[0].len() - 1, board.len() - 1);
+            assert!(
+                is_solvable(&board, Position::new(0, 0), goal),
+                "{algo:?} seed {seed} size {size} is not solvable"
+            );
+        }
+    }
+}
+
+#[test]
+fn astar_paths_are_as_short_as_breadth_first_ones() {
+    for algo in ALGORITHMS {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let board = convert_generated_maze(generate_maze_with(algo, 15, 11, &mut rng));
+            let mut braided = board.clone();
+            braid(&mut braided, 0.5, &mut rng);
+            for board in [board, braided] {
+                let (start, goal) = (Position::new(0, 0), Position::new(board[0].len() - 1, board.len() - 1));
+                let shortest = solve(&board, start, goal).map(|path| path.len());
+                for heuristic in [manhattan, euclidean] {
+                    let path = solve_astar(&board, start, goal, heuristic).unwrap();
+                    assert_eq!(Some(path.len()), shortest, "{algo:?} seed {seed}");
+                    assert!(path.windows(2).all(|w| manhattan(w[0], w[1]) == 1));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn depth_first_paths_are_valid_if_not_shortest() {
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = convert_generated_maze(generate_maze_with(MazeAlgorithm::Wilson, 15, 11, &mut rng));
+        braid(&mut board, 0.5, &mut rng);
+        let (start, goal) = (Position::new(0, 0), Position::new(board[0].len() - 1, board.len() - 1));
+        let path = solve_dfs(&board, start, goal).unwrap();
+        assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+        assert!(path.windows(2).all(|w| manhattan(w[0], w[1]) == 1));
+        assert!(path.iter().all(|p| board[p.y()][p.x()] != WALL));
+        assert!(path.len() >= solve(&board, start, goal).unwrap().len());
+    }
+}
+
+#[test]
+fn explored_paths_reach_the_goal() {
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = convert_generated_maze(generate_maze_with(MazeAlgorithm::Prim, 15, 11, &mut rng));
+        braid(&mut board, 0.5, &mut rng);
+        let (start, goal) = (Position::new(0, 0), Position::new(board[0].len() - 1, board.len() - 1));
+        let shortest = solve(&board, start, goal).map(|path| path.len());
+        for algorithm in [SearchAlgorithm::BreadthFirst, SearchAlgorithm::AStar, SearchAlgorithm::DepthFirst] {
+            let path = explore(&board, start, goal, algorithm, |_, _| ()).unwrap();
+            assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+            assert!(path.windows(2).all(|w| manhattan(w[0], w[1]) == 1));
+            match algorithm {
+                SearchAlgorithm::DepthFirst => assert!(Some(path.len()) >= shortest),
+                _ => assert_eq!(Some(path.len()), shortest, "{algorithm} seed {seed}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn exploration_frames_are_capped_and_end_on_the_goal() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let board = convert_generated_maze(generate_maze_with(MazeAlgorithm::Kruskal, 21, 21, &mut rng));
+    let goal = Position::new(20, 20);
+    let (frames, path) = exploration_frames(&board, Position::new(0, 0), goal, SearchAlgorithm::BreadthFirst, 10);
+    assert_eq!(frames.len(), 10);
+    assert!(frames.windows(2).all(|w| w[0].expanded.is_subset(&w[1].expanded)));
+    assert!(frames.last().unwrap().expanded.contains(&goal));
+    assert_eq!(path, solve(&board, Position::new(0, 0), goal));
+    assert_eq!("a*".parse::<SearchAlgorithm>(), Ok(SearchAlgorithm::AStar));
+}
+
+#[test]
+fn every_generation_cell_is_reachable() {
+    for algo in ALGORITHMS {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let generated = generate_maze_with(algo, 21, 13, &mut rng);
+            assert_eq!((generated[0].len(), generated.len()), (11, 7));
+
+            let board = convert_generated_maze(generated);
+            for y in (0..board.len()).step_by(2) {
+                for x in (0..board[0].len()).step_by(2) {
+                    assert!(
+                        is_solvable(&board, Position::new(0, 0), Position::new(x, y)),
+                        "{algo:?} seed {seed} never visited cell ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn stats_of_a_known_board() {
+    let board = vec![
+        vec![SYMBOL, WALL, GOAL],
+        vec![' ', WALL, ' '],
+        vec![' ', ' ', ' '],
+    ];
+    assert_eq!(analyze(&board), MazeStats { path_length: Some(6), dead_ends: 2 });
+
+    let walled_off = vec![vec![SYMBOL, WALL, GOAL]];
+    assert_eq!(analyze(&walled_off).path_length, None);
+}
+
+#[test]
+fn rectangular_games_are_solvable_with_goal_in_far_corner() {
+    for algo in ALGORITHMS {
+        for (width, height) in [(41, 5), (5, 41), (1, 9), (9, 1)] {
+            let state = GameState::with_options(algo, width, height, 0.0, &mut StdRng::seed_from_u64(3));
+            assert_eq!((state.width(), state.height()), render_dims(width, height));
+            assert_eq!(state.win_position, Position::new(state.width() - 1, state.height() - 1));
+            assert!(state.is_solvable(), "{algo:?} {width}x{height} is unsolvable");
+        }
+    }
+}
+
+#[test]
+fn generation_steps_end_on_the_generated_maze() {
+    for algo in ALGORITHMS {
+        let steps = generate_maze_steps(algo, 15, 9, &mut StdRng::seed_from_u64(6));
+        let maze = generate_maze_with(algo, 15, 9, &mut StdRng::seed_from_u64(6));
+        assert!(steps.len() > 2, "{algo:?} only took {} steps", steps.len());
+        assert_eq!(steps.last(), Some(&maze), "{algo:?}");
+    }
+}
+
+#[test]
+fn generation_frames_are_capped_and_end_on_the_maze() {
+    let setup = GameSetup { seed: 3, algorithm: MazeAlgorithm::Kruskal, width: 21, height: 21, ..GameSetup::default() };
+    let frames = setup.generation_frames(10);
+    assert_eq!(frames.len(), 10);
+    let mut last = frames.last().unwrap().clone();
+    let built = setup.build();
+    last[0][0] = SYMBOL;
+    last[20][20] = GOAL;
+    assert_eq!(&last[..], built.board());
+}
+
+#[test]
+fn setup_title_names_seed_algorithm_and_size() {
+    let mut setup = GameSetup { seed: 3, algorithm: MazeAlgorithm::Kruskal, width: 21, height: 11, ..GameSetup::default() };
+    assert_eq!(setup.title(), "Seed 3  Kruskal  21x11");
+    setup.wrap = true;
+    assert_eq!(setup.title(), "Seed 3  Wrapped  21x11");
+}
+
+#[test]
+fn longest_setups_put_the_goal_furthest_away() {
+    for seed in 0..10 {
+        let mut setup = GameSetup { seed, algorithm: MazeAlgorithm::Prim, width: 15, height: 11, ..GameSetup::default() };
+        let corner = setup.build().stats.unwrap().path_length.unwrap();
+        setup.longest = true;
+        let state = setup.build();
+        let furthest = state.stats.unwrap().path_length.unwrap();
+        assert!(furthest >= corner, "seed {seed}: {furthest} < {corner}");
+        let heat = HeatMap::new(state.board(), state.position, false);
+        assert_eq!(furthest, heat.max);
+    }
+}
+
+#[test]
+fn braiding_by_a_factor_that_isnt_finite_leaves_the_maze_alone() {
+    for factor in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut board = convert_generated_maze(generate_maze_with(MazeAlgorithm::Prim, 15, 15, &mut rng));
+        let perfect = board.clone();
+        braid(&mut board, factor, &mut rng);
+        assert_eq!(board, perfect, "factor {factor}");
+    }
+}
+
+/// Open cells on `board` reachable from the top left.
+fn reachable(board: &[Vec<char>]) -> usize {
+    let (width, height) = (board[0].len(), board.len());
+    let mut seen = vec![vec![false; width]; height];
+    let mut queue = VecDeque::from([(0usize, 0usize)]);
+    seen[0][0] = true;
+    let mut count = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        count += 1;
+        for (nx, ny) in [(x + 1, y), (x, y + 1), (x.wrapping_sub(1), y), (x, y.wrapping_sub(1))] {
+            if nx < width && ny < height && board[ny][nx] != WALL && !seen[ny][nx] {
+                seen[ny][nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    count
+}
+
+/// Generation cells, at even coordinates on the board, and the open
+/// passages joining them.
+fn cells_and_passages(board: &[Vec<char>]) -> (usize, usize) {
+    let (width, height) = (board[0].len(), board.len());
+    let cells = width.div_ceil(2) * height.div_ceil(2);
+    let passages = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| (x + y) % 2 == 1 && board[y][x] != WALL)
+        .count();
+    (cells, passages)
+}
+
+proptest! {
+    #[test]
+    fn unbraided_mazes_are_spanning_trees(algo in 0..ALGORITHMS.len(), seed: u64, width in 1..42usize, height in 1..42usize) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let board = convert_generated_maze(generate_maze_with(ALGORITHMS[algo], width, height, &mut rng));
+        let (cells, passages) = cells_and_passages(&board);
+        let open = board.iter().flatten().filter(|&&c| c != WALL).count();
+        // Every open square is a cell or a passage, so none sit between
+        // four cells, and all of them join up.
+        prop_assert_eq!(open, cells + passages);
+        prop_assert_eq!(passages, cells - 1, "a tree joins its cells with one fewer passage");
+        prop_assert_eq!(reachable(&board), open);
+    }
+
+    #[test]
+    fn braided_mazes_stay_connected(algo in 0..ALGORITHMS.len(), seed: u64, size in 1..42usize, factor in 0.0..=1.0f64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = convert_generated_maze(generate_maze_with(ALGORITHMS[algo], size, size, &mut rng));
+        braid(&mut board, factor, &mut rng);
+        let (cells, passages) = cells_and_passages(&board);
+        let open = board.iter().flatten().filter(|&&c| c != WALL).count();
+        // Loops are allowed, but not open squares between cells.
+        prop_assert_eq!(open, cells + passages);
+        prop_assert!(passages >= cells - 1);
+        prop_assert_eq!(reachable(&board), open);
+    }
+}
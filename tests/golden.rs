@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::Path;
+
+use mazeme::render::export_ascii;
+use mazeme::{GameSetup, MazeAlgorithm};
+
+const SEED: u64 = 20;
+
+/// Checks `algorithm`'s maze for `SEED` against `tests/golden/{name}.txt`.
+/// When a generator is meant to change, rerun with `UPDATE_GOLDEN=1` to
+/// write the new mazes and commit them.
+fn golden(name: &str, algorithm: MazeAlgorithm) {
+    let setup = GameSetup { seed: SEED, algorithm, width: 15, height: 9, ..GameSetup::default() };
+    let maze = export_ascii(setup.build().board());
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &maze).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+    assert!(maze == expected, "{algorithm} maze for seed {SEED} changed, expected\n{expected}\ngot\n{maze}");
+}
+
+#[test]
+fn backtracker_matches_golden() {
+    golden("backtracker", MazeAlgorithm::RecursiveBacktracker);
+}
+
+#[test]
+fn prim_matches_golden() {
+    golden("prim", MazeAlgorithm::Prim);
+}
+
+#[test]
+fn kruskal_matches_golden() {
+    golden("kruskal", MazeAlgorithm::Kruskal);
+}
+
+#[test]
+fn wilson_matches_golden() {
+    golden("wilson", MazeAlgorithm::Wilson);
+}
+
+#[test]
+fn eller_matches_golden() {
+    golden("eller", MazeAlgorithm::Eller);
+}
+
+#[test]
+fn division_matches_golden() {
+    golden("division", MazeAlgorithm::RecursiveDivision);
+}
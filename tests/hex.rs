@@ -0,0 +1,56 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use mazeme::{generate_hex_maze, render_hex, HexDirection, HexGame};
+
+#[test]
+fn every_hex_cell_is_reachable() {
+    for seed in 0..20 {
+        let maze = generate_hex_maze(7, 5, &mut StdRng::seed_from_u64(seed));
+        for row in 0..maze.rows() {
+            for col in 0..maze.cols() {
+                assert!(maze.solve((0, 0), (col, row)).is_some(), "seed {seed}: ({col}, {row}) unreachable");
+            }
+        }
+    }
+}
+
+#[test]
+fn hex_walls_agree_between_neighbors() {
+    let maze = generate_hex_maze(6, 6, &mut StdRng::seed_from_u64(4));
+    for row in 0..maze.rows() {
+        for col in 0..maze.cols() {
+            for dir in HexDirection::ALL {
+                if let Some((c, r)) = maze.neighbor((col, row), dir) {
+                    assert_eq!(
+                        maze.cells[row][col].has_wall(dir),
+                        maze.cells[r][c].has_wall(dir.opposite()),
+                        "({col}, {row}) {dir:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn hex_game_follows_the_solution_to_victory() {
+    let mut game = HexGame::new(5, 4, &mut StdRng::seed_from_u64(9));
+    let path = game.maze.solve(game.position, game.goal).unwrap();
+    for step in path.windows(2) {
+        let dir = HexDirection::ALL.into_iter().find(|&d| game.maze.open_neighbor(step[0], d) == Some(step[1])).unwrap();
+        game.move_position(dir);
+        assert_eq!(game.position, step[1]);
+    }
+    assert!(game.victory);
+    assert_eq!(game.moves, path.len() - 1);
+}
+
+#[test]
+fn render_hex_marks_player_and_goal() {
+    let maze = generate_hex_maze(3, 2, &mut StdRng::seed_from_u64(1));
+    let lines = render_hex(&maze, (0, 0), (2, 1));
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[1].chars().nth(2), Some('@'));
+    assert_eq!(lines[3].chars().nth(12), Some('G'));
+}
@@ -0,0 +1,58 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use mazeme::input::{stick_direction, Repeater};
+use mazeme::{KeyBindings, Movement};
+
+#[test]
+fn held_keys_repeat_until_they_go_quiet() {
+    let (tx, rx) = mpsc::channel();
+    let repeater = Repeater::start(Duration::from_millis(5), move |mv| tx.send(mv).is_ok());
+    assert!(repeater.press(Movement::RIGHT));
+    // The terminal's own repeats are swallowed while the key is held.
+    for _ in 0..10 {
+        thread::sleep(Duration::from_millis(20));
+        assert!(!repeater.press(Movement::RIGHT));
+    }
+    let repeats: Vec<Movement> = rx.try_iter().collect();
+    assert!(!repeats.is_empty() && repeats.iter().all(|&mv| mv == Movement::RIGHT));
+    thread::sleep(Duration::from_millis(300));
+    rx.try_iter().count();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.try_iter().count(), 0);
+    assert!(repeater.press(Movement::LEFT));
+}
+
+#[test]
+fn separate_taps_move_once_each() {
+    for interval in [80, 200] {
+        let (tx, rx) = mpsc::channel();
+        let repeater = Repeater::start(Duration::from_millis(interval), move |mv| tx.send(mv).is_ok());
+        let mut moves = 0;
+        for _ in 0..2 {
+            moves += usize::from(repeater.press(Movement::DOWN));
+            thread::sleep(Duration::from_millis(150));
+        }
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(moves + rx.try_iter().count(), 2, "at {interval}ms");
+    }
+}
+
+#[test]
+fn sticks_point_the_way_they_lean_furthest() {
+    assert_eq!(stick_direction(0.1, -0.3), None);
+    assert_eq!(stick_direction(0.9, 0.6), Some(Movement::RIGHT));
+    assert_eq!(stick_direction(-0.2, 0.7), Some(Movement::UP));
+    assert_eq!(stick_direction(0.4, -0.8), Some(Movement::DOWN));
+    assert_eq!(stick_direction(-1.0, 0.0), Some(Movement::LEFT));
+}
+
+#[test]
+fn layouts_clashing_with_action_keys_are_refused() {
+    assert_eq!(KeyBindings::from_layout(",aoe"), None);
+    let keys = KeyBindings { save: 'S', ..KeyBindings::default() }.with_layout(",aoe").unwrap();
+    assert_eq!(keys.movement('o'), Some(Movement::DOWN));
+    assert_eq!(KeyBindings::default().clash(), None);
+    assert_eq!(KeyBindings { undo: 'q', ..KeyBindings::default() }.clash(), Some('q'));
+}
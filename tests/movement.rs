@@ -0,0 +1,496 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use mazeme::game_state::solve::{path_cost, solve_nearest_wrapping};
+use mazeme::game_state::{StateEvent, SYMBOL, WALL};
+use mazeme::{GameSetup, GameState, GameStateHandler, Movement, Position, Walls};
+
+/// A 3x3 board with the player top left, a wall to its right and the goal
+/// top right, reachable around the bottom.
+fn walled_state() -> GameState {
+    let board = vec![
+        vec![' ', WALL, ' '],
+        vec![' ', WALL, ' '],
+        vec![' ', ' ', ' '],
+    ];
+    GameState::from_board(board, Position::new(0, 0), Position::new(2, 0))
+}
+
+#[test]
+fn moving_into_a_wall_is_a_no_op() {
+    let mut state = walled_state();
+    let board = state.board().to_vec();
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.position, Position::new(0, 0));
+    assert_eq!(state.board(), &board[..]);
+    assert_eq!(state.moves, 0);
+}
+
+#[test]
+fn moving_off_the_board_is_a_no_op() {
+    let mut state = walled_state();
+    state.move_position(Movement::UP);
+    state.move_position(Movement::LEFT);
+    assert_eq!(state.position, Position::new(0, 0));
+    assert_eq!(state.moves, 0);
+}
+
+#[test]
+fn blocked_moves_are_counted_as_bumps() {
+    let mut state = walled_state();
+    state.move_position(Movement::RIGHT);
+    state.move_position(Movement::UP);
+    assert_eq!(state.bumps, 2);
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.bumps, 2);
+}
+
+#[test]
+fn moving_into_an_open_cell_updates_position_and_board() {
+    let mut state = walled_state();
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.position, Position::new(0, 1));
+    assert_eq!(state.board()[0][0], ' ');
+    assert_eq!(state.board()[1][0], SYMBOL);
+    assert_eq!(state.moves, 1);
+}
+
+#[test]
+fn movement_is_blocked_after_victory() {
+    let mut state = walled_state();
+    state.victory = true;
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.position, Position::new(0, 0));
+    assert_eq!(state.board()[0][0], SYMBOL);
+}
+
+#[test]
+fn reaching_the_goal_wins() {
+    let mut state = walled_state();
+    for mv in [Movement::DOWN, Movement::DOWN, Movement::RIGHT, Movement::RIGHT, Movement::UP, Movement::UP] {
+        state.move_position(mv);
+    }
+    assert_eq!(state.position, Position::new(2, 0));
+    assert!(state.victory);
+}
+
+#[test]
+fn hint_points_at_the_next_step_until_moving() {
+    let mut state = walled_state();
+    state.show_hint();
+    assert_eq!(state.hint.map(|(pos, _)| pos), Some(Position::new(0, 1)));
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.hint, None);
+}
+
+#[test]
+fn solution_path_follows_the_player_until_hidden() {
+    let mut state = walled_state();
+    state.toggle_solution_path();
+    assert_eq!(state.solution_path.as_ref().map(|p| p.len()), Some(7));
+    state.move_position(Movement::DOWN);
+    let path = state.solution_path.clone().unwrap();
+    assert_eq!(path.len(), 6);
+    assert!(!path.contains(&Position::new(0, 0)));
+    state.toggle_solution_path();
+    assert_eq!(state.solution_path, None);
+}
+
+#[test]
+fn wrapped_moves_cross_the_edges() {
+    let board = vec![
+        vec![' ', ' ', ' ', ' '],
+        vec![WALL, WALL, WALL, WALL],
+        vec![' ', ' ', ' ', ' '],
+        vec![' ', WALL, WALL, WALL],
+    ];
+    let mut state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 2));
+    state.wrap = true;
+    state.move_position(Movement::LEFT);
+    assert_eq!(state.position, Position::new(3, 0));
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.position, Position::new(0, 0));
+    state.move_position(Movement::UP);
+    assert_eq!(state.position, Position::new(0, 3));
+    assert_eq!(state.solution().map(|path| path.len() - 1), Some(3));
+}
+
+#[test]
+fn wrapped_mazes_are_solvable() {
+    for seed in 0..20 {
+        let state = GameState::wrapped(15, 9, &mut StdRng::seed_from_u64(seed));
+        assert_eq!((state.width(), state.height()), (16, 10));
+        assert!(state.is_solvable(), "seed {seed} is unsolvable");
+        for y in (0..state.height()).step_by(2) {
+            for x in (0..state.width()).step_by(2) {
+                let path = solve_nearest_wrapping(state.board(), state.position, &[Position::new(x, y)]);
+                assert!(path.is_some(), "seed {seed} never visited ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn endpoints_can_be_moved_but_not_onto_walls() {
+    let state = walled_state().with_endpoints(Position::new(2, 2), Position::new(0, 0)).unwrap();
+    assert_eq!((state.position, state.win_position), (Position::new(2, 2), Position::new(0, 0)));
+    assert_eq!(state.goals, vec![Position::new(0, 0)]);
+    assert_eq!(state.board()[2][2], SYMBOL);
+    assert_eq!(state.board()[0][2], ' ');
+    assert_eq!(state.stats.unwrap().path_length, Some(4));
+
+    assert!(walled_state().with_endpoints(Position::new(1, 0), Position::new(2, 0)).is_err());
+    assert!(walled_state().with_endpoints(Position::new(0, 0), Position::new(3, 0)).is_err());
+    assert!(walled_state().with_endpoints(Position::new(0, 2), Position::new(0, 2)).is_err());
+}
+
+#[test]
+fn going_over_par_fails_the_attempt_but_not_the_game() {
+    let mut state = walled_state();
+    state.set_par(1.0);
+    assert_eq!(state.par, Some(6));
+    for mv in [Movement::DOWN, Movement::UP, Movement::DOWN, Movement::DOWN, Movement::RIGHT, Movement::RIGHT] {
+        state.move_position(mv);
+    }
+    assert!(!state.over_par());
+    state.move_position(Movement::UP);
+    assert!(state.over_par());
+    assert!(!state.failure);
+    state.move_position(Movement::UP);
+    assert!(state.victory);
+}
+
+/// Follows the solution until the game is won, returning the moves taken.
+fn walk_solution(state: &mut GameState) -> usize {
+    while !state.victory {
+        let path = state.solution().unwrap();
+        state.move_position(Movement::between(path[0], path[1]).unwrap());
+    }
+    state.moves
+}
+
+#[test]
+fn par_counts_a_portal_hop_as_one_move() {
+    let mut state = GameSetup { portals: 3, ..GameSetup::default() }.build();
+    let fewest = state.fewest_moves().unwrap();
+    assert!(fewest < state.solution().unwrap().len() - 1);
+    state.set_par(1.0);
+    assert_eq!(state.par, Some(fewest));
+    assert_eq!(walk_solution(&mut state), fewest);
+    assert!(!state.over_par());
+}
+
+#[test]
+fn par_counts_a_diagonal_as_one_move() {
+    let mut state = GameSetup { diagonals: true, ..GameSetup::default() }.build();
+    let fewest = state.fewest_moves().unwrap();
+    assert!(fewest < state.stats.unwrap().path_length.unwrap());
+    state.set_par(1.0);
+    assert_eq!(walk_solution(&mut state), fewest);
+    assert!(!state.over_par());
+}
+
+#[test]
+fn fewest_moves_collects_every_goal() {
+    let mut state = GameSetup { seed: 3, goals: 3, ..GameSetup::default() }.build();
+    let fewest = state.fewest_moves().unwrap();
+    assert!(fewest > state.solution().unwrap().len() - 1);
+    assert_eq!(walk_solution(&mut state), fewest);
+}
+
+#[test]
+fn portals_can_drop_the_player_on_the_goal() {
+    let mut state = walled_state();
+    state.portals.insert(Position::new(0, 1), Position::new(2, 0));
+    assert_eq!(state.solution(), Some(vec![Position::new(0, 0), Position::new(0, 1), Position::new(2, 0)]));
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.position, Position::new(2, 0));
+    assert_eq!(state.board()[1][0], ' ');
+    assert_eq!(state.moves, 1);
+    assert!(state.victory);
+}
+
+#[test]
+fn one_way_gates_only_open_in_their_direction() {
+    let mut state = walled_state();
+    state.one_way.insert(Position::new(1, 2), Movement::RIGHT);
+    state.move_position(Movement::DOWN);
+    state.move_position(Movement::DOWN);
+    state.move_position(Movement::RIGHT);
+    state.move_position(Movement::LEFT);
+    assert_eq!(state.position, Position::new(1, 2));
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.position, Position::new(2, 2));
+    state.move_position(Movement::LEFT);
+    assert_eq!(state.position, Position::new(2, 2));
+    assert_eq!(state.bumps, 2);
+
+    let mut blocked = walled_state();
+    blocked.one_way.insert(Position::new(1, 2), Movement::LEFT);
+    assert_eq!(blocked.solution(), None);
+}
+
+/// A 5x3 board, player top left and goal bottom right, with a portal from
+/// the top row to the bottom one and ice either side of it.
+fn icy_state() -> GameState {
+    let board = vec![
+        vec![' ', ' ', ' ', ' ', ' '],
+        vec![WALL, WALL, WALL, WALL, ' '],
+        vec![' ', ' ', ' ', ' ', ' '],
+    ];
+    let mut state = GameState::from_board(board, Position::new(0, 0), Position::new(4, 2));
+    state.portals.insert(Position::new(2, 0), Position::new(0, 2));
+    state.ice.extend([(1, 0), (0, 2), (1, 2), (2, 2), (3, 2)].map(|(x, y)| Position::new(x, y)));
+    state
+}
+
+#[test]
+fn ice_slides_through_portals_onto_the_goal() {
+    let mut state = icy_state();
+    assert_eq!(state.solution().map(|path| path.len()), Some(8));
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.position, Position::new(4, 2));
+    assert_eq!(state.moves, 1);
+    assert!(state.victory);
+}
+
+#[test]
+fn ice_slides_stop_at_walls() {
+    let mut state = icy_state();
+    state.portals.clear();
+    state.ice.insert(Position::new(2, 0));
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.position, Position::new(3, 0));
+    state.undo();
+    assert_eq!(state.position, Position::new(0, 0));
+}
+
+#[test]
+fn the_first_player_to_the_goal_wins_a_race() {
+    let mut state = walled_state();
+    state.add_rival();
+    for mv in [Movement::DOWN, Movement::DOWN, Movement::RIGHT, Movement::RIGHT, Movement::UP] {
+        state.move_player(1, mv);
+    }
+    state.move_player(0, Movement::DOWN);
+    assert_eq!(state.winner, None);
+    state.move_player(1, Movement::UP);
+    assert!(state.victory);
+    assert_eq!(state.winner, Some(1));
+    assert_eq!(state.rival.map(|rival| (rival.position, rival.moves)), Some((Position::new(2, 0), 6)));
+    state.move_player(0, Movement::DOWN);
+    assert_eq!(state.position, Position::new(0, 1));
+}
+
+#[test]
+fn diagonals_round_corners_when_enabled() {
+    let mut state = walled_state();
+    state.move_position(Movement::DOWN);
+    state.move_position(Movement::DOWN_RIGHT);
+    assert_eq!(state.position, Position::new(0, 1));
+    assert_eq!(state.bumps, 1);
+
+    state.diagonals = true;
+    assert_eq!(state.solution().map(|path| path.len()), Some(4));
+    state.move_position(Movement::DOWN_RIGHT);
+    assert_eq!(state.position, Position::new(1, 2));
+    state.move_position(Movement::UP_RIGHT);
+    state.move_position(Movement::UP);
+    assert!(state.victory);
+    assert_eq!(state.moves, 4);
+}
+
+#[test]
+fn diagonals_cannot_squeeze_between_walls() {
+    let board = vec![
+        vec![' ', WALL],
+        vec![WALL, ' '],
+    ];
+    let mut state = GameState::from_board(board, Position::new(0, 0), Position::new(1, 1));
+    state.diagonals = true;
+    state.move_position(Movement::DOWN_RIGHT);
+    assert_eq!(state.position, Position::new(0, 0));
+    assert_eq!(state.solution(), None);
+}
+
+#[test]
+fn hardcore_trails_cannot_be_recrossed() {
+    let mut state = walled_state();
+    state.hardcore = true;
+    state.move_position(Movement::DOWN);
+    state.move_position(Movement::UP);
+    state.undo();
+    assert_eq!(state.position, Position::new(0, 1));
+    assert!(!state.failure);
+
+    let board = vec![vec![' ', ' ', WALL, ' ']];
+    let mut boxed = GameState::from_board(board, Position::new(0, 0), Position::new(3, 0));
+    boxed.hardcore = true;
+    boxed.move_position(Movement::RIGHT);
+    assert!(boxed.failure);
+}
+
+#[test]
+fn countdown_holds_moves_and_the_clock_until_go() {
+    let mut state = walled_state();
+    let mut rng = StdRng::seed_from_u64(1);
+    let time = state.time_remaining;
+    state.start_countdown(2);
+    assert_eq!(state.countdown(), Some(2));
+    for _ in 0..10 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.countdown(), Some(1));
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.position, Position::new(0, 0));
+    for _ in 0..10 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.countdown(), Some(0));
+    assert_eq!(state.time_remaining, time);
+    state.move_position(Movement::DOWN);
+    assert_eq!(state.position, Position::new(0, 1));
+    for _ in 0..10 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.countdown(), None);
+    assert_eq!(state.time_remaining, time - 1);
+}
+
+#[test]
+fn mud_costs_moves_and_is_routed_around() {
+    let board = vec![
+        vec![' ', ' ', ' '],
+        vec![' ', WALL, ' '],
+        vec![' ', ' ', ' '],
+    ];
+    let mut state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 0));
+    state.costs.insert(Position::new(1, 0), 9);
+    let path = state.solution().unwrap();
+    assert_eq!(path.len(), 7);
+    assert_eq!(path_cost(&path, &state.costs), 6);
+
+    state.move_position(Movement::RIGHT);
+    assert_eq!(state.moves, 9);
+    state.undo();
+    assert_eq!(state.moves, 0);
+    state.costs.insert(Position::new(1, 0), 3);
+    assert_eq!(state.solution().map(|path| path.len()), Some(3));
+}
+
+#[test]
+fn copying_without_a_clipboard_reports_why() {
+    let (tx, rx) = mpsc::channel();
+    let handler = GameStateHandler::new(GameState::new_seeded(5, 3), tx);
+    handler.get_sender().send(StateEvent::Copy).unwrap();
+    // Either way the game carries on, saying what happened.
+    let message = rx.iter().find_map(|state| state.message).unwrap();
+    assert!(message.starts_with("Copied") || message.starts_with("Copy failed"));
+    handler.get_sender().send(StateEvent::NoOP).unwrap();
+    assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+}
+
+#[test]
+fn restarting_after_a_win_starts_the_same_maze_over() {
+    let initial = walled_state();
+    let mut state = initial.clone();
+    state.show_trail = true;
+    for mv in [Movement::DOWN, Movement::DOWN, Movement::RIGHT, Movement::RIGHT, Movement::UP, Movement::UP] {
+        state.move_position(mv);
+    }
+    assert!(state.victory);
+    state.restart(&initial);
+    assert!(!state.victory);
+    assert_eq!(state.position, Position::new(0, 0));
+    assert_eq!(state.board(), initial.board());
+    assert_eq!(state.moves, 0);
+    assert!(state.visited.len() <= 1 && state.history.is_empty());
+    assert!(state.show_trail);
+
+    state.add_rival();
+    state.move_position(Movement::DOWN);
+    state.restart(&initial);
+    assert_eq!(state.position, Position::new(0, 1));
+}
+
+#[test]
+fn quitting_stops_the_game() {
+    let (tx, rx) = mpsc::channel();
+    let handler = GameStateHandler::new(GameState::new_seeded(5, 3), tx);
+    handler.get_sender().send(StateEvent::Quit).unwrap();
+    // The states run out once the game has stopped, even with it still
+    // being sent events.
+    let _ = handler.get_sender().send(StateEvent::NoOP);
+    while rx.recv_timeout(Duration::from_secs(5)).is_ok() {}
+    assert!(rx.try_recv().is_err_and(|e| e == mpsc::TryRecvError::Disconnected));
+}
+
+#[test]
+fn cells_report_their_walls() {
+    let mut state = walled_state();
+    let cells: Vec<_> = state.cells().collect();
+    assert_eq!(cells.len(), 7);
+    assert_eq!(cells[0], (Position::new(0, 0), Walls { up: true, down: false, left: true, right: true }));
+    assert_eq!(state.walls(Position::new(1, 2)), Walls { up: true, down: true, left: false, right: false });
+    assert!(state.walls(Position::new(1, 2)).blocks(Movement::UP));
+
+    state.wrap = true;
+    assert_eq!(state.walls(Position::new(0, 0)), Walls { up: false, down: false, left: false, right: true });
+}
+
+#[test]
+fn only_open_neighbors_are_connected() {
+    let mut state = walled_state();
+    assert!(state.are_connected(Position::new(0, 0), Position::new(0, 1)));
+    assert!(state.are_connected(Position::new(0, 1), Position::new(0, 0)));
+    assert!(!state.are_connected(Position::new(0, 0), Position::new(1, 0)));
+    assert!(!state.are_connected(Position::new(0, 0), Position::new(0, 2)));
+    assert!(!state.are_connected(Position::new(0, 1), Position::new(1, 2)));
+    assert!(!state.are_connected(Position::new(0, 0), Position::new(0, 0)));
+    assert!(!state.are_connected(Position::new(2, 2), Position::new(3, 2)));
+
+    state.wrap = true;
+    assert!(state.are_connected(Position::new(0, 0), Position::new(2, 0)));
+    assert!(state.are_connected(Position::new(0, 0), Position::new(0, 2)));
+    assert!(!state.are_connected(Position::new(2, 2), Position::new(3, 2)));
+}
+
+#[test]
+fn closing_fog_loses_the_game_once_it_reaches_the_player() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut state = walled_state();
+    state.visibility_radius = Some(2);
+    state.fog_closing = Some(2);
+    assert_eq!(state.fog_closes_in(), Some(2));
+    for _ in 0..20 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.visibility_radius, Some(1));
+    assert!(!state.failure);
+    for _ in 0..15 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.fog_closes_in(), Some(1));
+    for _ in 0..5 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.visibility_radius, Some(0));
+    assert!(state.failure);
+    assert_eq!(state.fog_closes_in(), None);
+}
+
+#[test]
+fn fog_closing_slowly_does_not_overflow() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut state = walled_state();
+    state.visibility_radius = Some(2);
+    state.fog_closing = Some(u64::MAX);
+    for _ in 0..20 {
+        state.tick(&mut rng);
+    }
+    assert_eq!(state.visibility_radius, Some(2));
+    assert!(state.fog_closes_in().is_some());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "serde")]
+
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use mazeme::game_state::net::Peer;
+use mazeme::game_state::StateEvent;
+use mazeme::{GameSetup, MazeAlgorithm, Movement};
+
+fn setup() -> GameSetup {
+    GameSetup { seed: 5, algorithm: MazeAlgorithm::Wilson, width: 9, height: 9, ..GameSetup::default() }
+}
+
+#[test]
+fn joining_shares_the_maze_and_relays_moves() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = thread::spawn(move || Peer::host(&listener, setup()).unwrap());
+    let guest = Peer::join(addr).unwrap();
+    let host = host.join().unwrap();
+    assert_eq!(guest.setup, setup());
+
+    let (states, host_states) = mpsc::channel();
+    let (host_render, rendered) = mpsc::channel();
+    let (host_events, _) = mpsc::channel();
+    host.start(host_states, host_render, host_events);
+    let (_guest_states, guest_states) = mpsc::channel();
+    let (guest_render, _) = mpsc::channel();
+    let (guest_events, received) = mpsc::channel();
+    guest.start(guest_states, guest_render, guest_events);
+
+    let mut state = setup().build();
+    state.add_rival();
+    state.move_position(Movement::DOWN);
+    let moved_to = state.position;
+    states.send(state).unwrap();
+    assert!(rendered.recv_timeout(Duration::from_secs(5)).is_ok());
+    let event = received.recv_timeout(Duration::from_secs(5));
+    assert!(matches!(event, Ok(StateEvent::RivalMoved(pos)) if pos == moved_to));
+}
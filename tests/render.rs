@@ -0,0 +1,321 @@
+use mazeme::render::{
+    draw_frame, draw_frame_with, export_ascii, fit_cell, import_ascii, start_render, strip_colors, Glyphs,
+    RenderConfig, Viewport, WallStyle,
+};
+#[cfg(feature = "serde")]
+use mazeme::render::stream::Record;
+use mazeme::{GameState, KeyBindings, Position};
+
+#[test]
+fn tiny_terminal_does_not_underflow() {
+    let state = GameState::new_seeded(25, 7);
+    for dims in [(0, 0), (1, 1), (3, 2), (10, 5)] {
+        let view = Viewport::centered(&state, dims);
+        assert!(view.width >= 1 && view.height >= 1);
+        assert!(view.left + view.width <= state.width());
+        assert!(view.top + view.height <= state.height());
+        let (x, y) = view.origin(dims);
+        assert!(x <= dims.0 && y <= dims.1);
+    }
+}
+
+#[test]
+fn board_that_fits_is_centered_whole() {
+    let state = GameState::new_seeded(9, 7);
+    let view = Viewport::centered(&state, (80, 24));
+    assert_eq!((view.left, view.top, view.width, view.height), (0, 0, 9, 9));
+    assert_eq!(view.origin((80, 24)), (26, 8));
+}
+
+#[test]
+fn frame_draws_board_player_and_goal() {
+    let state = GameState::new_seeded(5, 3);
+    let mut out = Vec::new();
+    draw_frame(&mut out, &state, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("\x1b[2J"));
+    assert!(frame.contains("◀◆▶"));
+    assert!(frame.contains("\x1b[35m▓▓▓\x1b[0m"));
+    assert!(frame.contains(&format!("{:03}", state.time_remaining)));
+    assert!(frame.contains("Moves: 0"));
+}
+
+#[test]
+fn frame_without_color_has_no_sgr_sequences() {
+    let state = GameState::new_seeded(5, 3);
+    let config = RenderConfig { color: false, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert_eq!(frame, strip_colors(&frame));
+    assert!(frame.contains(" @ "));
+    assert!(frame.contains("[G]"));
+}
+
+#[test]
+fn custom_glyphs_fill_three_columns() {
+    assert_eq!(fit_cell("@", false), " @ ");
+    assert_eq!(fit_cell("G", true), "GGG");
+    assert_eq!(fit_cell("🏁", true), "🏁 ");
+    assert_eq!(fit_cell("<o>", false), "<o>");
+    assert_eq!(fit_cell("🐭🐭", false), "🐭 ");
+    assert_eq!(fit_cell("goal", true), "goa");
+
+    let state = GameState::new_seeded(5, 3);
+    let glyphs = Glyphs { player: Some("🐭".to_string()), goal: Some("🧀".to_string()) };
+    let config = RenderConfig { color: false, glyphs, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("🐭 ") && frame.contains("🧀 "));
+    assert!(!frame.contains(" @ ") && !frame.contains("[G]"));
+}
+
+#[test]
+fn wide_characters_keep_rows_aligned() {
+    let mut state = GameState::new_seeded(5, 3);
+    let open = (0..state.width()).find(|&x| state.board[2][x] == ' ').unwrap();
+    state.board[2][open] = '漢';
+    let config = RenderConfig { color: false, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    let row = frame.split("\x1b[").find(|seq| seq.contains('漢')).unwrap();
+    let row = &row[row.find('H').unwrap() + 1..];
+    assert!(row.contains("漢 "));
+    assert_eq!(unicode_width::UnicodeWidthStr::width(row), 3 * state.width() + 2);
+}
+
+#[test]
+fn line_walls_join_their_neighbors() {
+    let board = vec![vec![' ', '░', ' '], vec!['░', '░', '░'], vec![' ', '░', ' ']];
+    let state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 2));
+    let config = RenderConfig { color: false, walls: WallStyle::Lines, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("│ @  │    │"));
+    assert!(frame.contains("│────┼────│"));
+    assert!(frame.contains('┌') && frame.contains('┘'));
+    assert!(!frame.contains('░'));
+    assert_eq!("lines".parse::<WallStyle>(), Ok(WallStyle::Lines));
+}
+
+#[test]
+fn frame_rows_are_cursor_positioned() {
+    let state = GameState::new_seeded(5, 3);
+    let mut out = Vec::new();
+    draw_frame(&mut out, &state, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    let (x, y) = Viewport::centered(&state, (80, 24)).origin((80, 24));
+    for row in y..y + state.height() + 2 {
+        assert!(frame.contains(&format!("\x1b[{row};{x}H")));
+    }
+}
+
+#[test]
+fn heat_map_shades_cells_from_near_to_far() {
+    let mut state = GameState::new_seeded(9, 4);
+    state.toggle_heat_map();
+    let heat = state.heat_map.clone().unwrap();
+    let far = *heat.distances.iter().max_by_key(|&(_, &d)| d).unwrap().0;
+    assert_eq!(heat.level(far, 21), Some(20));
+    assert_eq!(heat.level(state.position, 21), Some(0));
+
+    let mut out = Vec::new();
+    draw_frame(&mut out, &state, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("\x1b[48;5;21m   "));
+    state.toggle_heat_map();
+    assert_eq!(state.heat_map, None);
+}
+
+#[test]
+fn victory_banner_uses_win_text_and_shrinks_to_fit() {
+    let mut state = GameState::new_seeded(5, 3);
+    state.victory = true;
+    let config = RenderConfig { color: false, win_text: Some("GG".to_string()), ..RenderConfig::default() };
+    let art = figlet_rs::FIGfont::standard().unwrap().convert("GG").unwrap().to_string();
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(art.lines().all(|l| frame.contains(l)));
+
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (10, 5)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("GG"));
+    assert!(!art.lines().filter(|l| !l.trim().is_empty()).any(|l| frame.contains(l)));
+}
+
+#[test]
+fn victory_banner_is_centered_by_chars_on_screen() {
+    let mut state = GameState::new_seeded(5, 3);
+    state.victory = true;
+    let config = RenderConfig { color: false, win_text: Some("★★".to_string()), ..RenderConfig::default() };
+    for dims in [(1, 1), (2, 3), (80, 24)] {
+        let mut out = Vec::new();
+        draw_frame_with(&mut out, &state, &config, dims).unwrap();
+        let frame = String::from_utf8(out).unwrap();
+        assert!(!frame.contains("\x1b[0;") && !frame.contains(";0H"));
+    }
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    // Two columns wide, though six bytes long.
+    assert!(frame.contains("\x1b[12;40H★★"));
+}
+
+#[test]
+fn legend_follows_key_bindings_and_hides_on_banners() {
+    let mut state = GameState::new_seeded(5, 3);
+    let keys = KeyBindings::from_layout("ijkl").unwrap();
+    assert!(keys.legend().starts_with("IJKL: move"));
+    assert!(keys.legend().contains("space: pause"));
+    let config = RenderConfig { legend: Some(keys.legend()), ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains(&keys.legend()));
+
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 8)).unwrap();
+    assert!(!String::from_utf8(out).unwrap().contains("IJKL"));
+
+    state.victory = true;
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    assert!(!String::from_utf8(out).unwrap().contains(",AOE"));
+}
+
+#[test]
+fn header_sits_above_the_board_and_is_cut_to_fit() {
+    let state = GameState::new_seeded(5, 3);
+    let config = RenderConfig { header: Some("Seed 3  Prim  5x5".to_string()), ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let (_, y) = Viewport::centered(&state, (80, 24)).origin((80, 24));
+    assert!(String::from_utf8(out).unwrap().contains(&format!("\x1b[{};32HSeed 3  Prim  5x5", y - 1)));
+
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (6, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("HSeed 3") && !frame.contains("Seed 3 "));
+}
+
+#[test]
+fn exported_mazes_load_back_unchanged() {
+    let state = GameState::new_seeded(9, 4);
+    let loaded = import_ascii(&export_ascii(state.board())).unwrap();
+    assert_eq!(loaded.board(), state.board());
+    assert_eq!((loaded.position, loaded.win_position), (state.position, state.win_position));
+}
+
+#[test]
+fn hand_drawn_mazes_load_one_char_per_cell() {
+    let state = import_ascii("●  \n## \n▓  \n").unwrap();
+    assert_eq!(state.board()[1], vec!['░', '░', ' ']);
+    assert_eq!(state.win_position, mazeme::Position::new(0, 2));
+    assert_eq!(state.solution().map(|path| path.len()), Some(7));
+}
+
+#[test]
+fn bad_ascii_mazes_are_explained() {
+    let err = |text: &str| import_ascii(text).unwrap_err();
+    assert_eq!(err(""), "the maze is empty");
+    assert_eq!(err("● \n▓\n"), "row 2 is 1 cells wide, expected 2");
+    assert_eq!(err("●x▓\n"), "unknown character 'x' at row 1, column 2");
+    assert_eq!(err("●●▓\n"), "expected one start and one goal, found 2 and 1");
+    assert_eq!(err("●#▓\n"), "the goal can't be reached from the start");
+    assert_eq!(err("░░░░░\n░ ▓ ░\n"), "the maze must be surrounded by a border");
+}
+
+#[test]
+fn renderer_stops_cleanly_once_nothing_can_send() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let renderer = start_render(rx, RenderConfig::default());
+    drop(tx);
+    let waited = std::time::Instant::now();
+    while renderer.is_running() && waited.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::yield_now();
+    }
+    assert!(!renderer.is_running());
+    assert_eq!(renderer.crash(), None);
+}
+
+#[test]
+fn compact_cells_are_one_column_wide() {
+    let board = vec![vec![' ', '░', ' '], vec!['░', '░', '░'], vec![' ', '░', ' ']];
+    let state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 2));
+    let glyphs = Glyphs { player: Some("🐭".to_string()), goal: Some("G".to_string()) };
+    let config = RenderConfig { color: false, compact: true, glyphs, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("░@░ ░"));
+    assert!(frame.contains("░ ░G░"));
+    assert!(!frame.contains('🐭'));
+}
+
+#[test]
+fn zooming_steps_through_cell_widths_keeping_the_player_in_view() {
+    let widths = |compact| (0..4).map(move |zoom| RenderConfig { compact, zoom, ..RenderConfig::default() }.cell_width());
+    assert_eq!(widths(false).collect::<Vec<_>>(), [3, 2, 1, 3]);
+    assert_eq!(widths(true).collect::<Vec<_>>(), [1, 3, 2, 1]);
+
+    let board = vec![vec![' ', '░', ' '], vec!['░', '░', '░'], vec![' ', '░', ' ']];
+    let state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 2));
+    let config = RenderConfig { color: false, zoom: 1, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("░ @░░  ░"));
+
+    let mut state = GameState::new_seeded(99, 3);
+    state.position = Position::new(60, 0);
+    for cell_width in [3, 2, 1] {
+        let view = Viewport::centered_with(&state, (40, 24), cell_width, 1);
+        assert_eq!(view.width, 38 / cell_width);
+        assert!((view.left..view.left + view.width).contains(&60));
+    }
+}
+
+#[test]
+fn half_blocks_draw_two_rows_a_line() {
+    let board = vec![vec![' ', '░', ' '], vec!['░', '░', ' '], vec![' ', '░', ' ']];
+    let state = GameState::from_board(board, Position::new(0, 0), Position::new(2, 2));
+    let config = RenderConfig { color: false, compact: true, half_blocks: true, ..RenderConfig::default() };
+    let mut out = Vec::new();
+    draw_frame_with(&mut out, &state, &config, (80, 24)).unwrap();
+    let frame = String::from_utf8(out).unwrap();
+    assert!(frame.contains("░@█ ░"));
+    assert!(frame.contains("░ ▀G░"));
+    let view = Viewport::centered_with(&state, (80, 24), 1, 2);
+    assert_eq!((view.height, view.lines()), (3, 2));
+}
+
+#[test]
+fn closing_fog_is_warned_of_on_the_status_line() {
+    let mut state = GameState::new_seeded(7, 3);
+    state.visibility_radius = Some(3);
+    state.fog_closing = Some(5);
+    let status = |state: &GameState| {
+        let mut out = Vec::new();
+        draw_frame_with(&mut out, state, &RenderConfig { color: false, ..RenderConfig::default() }, (80, 24)).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert!(status(&state).contains("Fog: 3, closing in 5s"));
+    state.visibility_radius = Some(1);
+    assert!(status(&state).contains("Fog: 1, blind in 5s!"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn stream_records_are_single_lines() {
+    let state = GameState::new_seeded(7, 3);
+    let record = Record::of(&state);
+    assert_eq!(record.frame, export_ascii(state.board()));
+    assert!(record.status.starts_with("Moves: 0"));
+    let line = record.to_line();
+    assert!(!line.contains('\n'));
+    assert_eq!(Record::parse(&line), Ok(record));
+    assert!(Record::parse("not a frame").is_err());
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "serde")]
+
+use std::time::Duration;
+
+use mazeme::game_state::replay::{Recorder, Replay, ReplayEvent, ReplayHeader};
+use mazeme::game_state::StateEvent;
+use mazeme::{GameSetup, MazeAlgorithm, Movement};
+
+fn setup() -> GameSetup {
+    GameSetup { seed: 11, algorithm: MazeAlgorithm::Prim, width: 9, height: 7, braid: 0.3, goals: 2, items: 3, enemies: 1, ..GameSetup::default() }
+}
+
+#[test]
+fn same_setup_builds_the_same_game() {
+    let (a, b) = (setup().build(), setup().build());
+    assert_eq!(a.board(), b.board());
+    assert_eq!(a.goals, b.goals);
+    assert_eq!(a.items, b.items);
+    assert_eq!(a.enemies, b.enemies);
+}
+
+#[test]
+fn recording_round_trips() {
+    let path = std::env::temp_dir().join(format!("mazeme_replay_{}.jsonl", std::process::id()));
+    let header = ReplayHeader { setup: setup(), enemy_seed: 5, fog: Some(3), fog_closing: None, par: None, countdown: 0 };
+    let mut recorder = Recorder::create(&path, &header).unwrap();
+    for ev in [StateEvent::Tick, StateEvent::Movement(Movement::RIGHT), StateEvent::Solve, StateEvent::Undo] {
+        recorder.record(&ev).unwrap();
+    }
+    drop(recorder);
+
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.header, header);
+    let events: Vec<ReplayEvent> = replay.events.iter().map(|&(_, ev)| ev).collect();
+    assert_eq!(events, [ReplayEvent::Tick, ReplayEvent::Movement(Movement::RIGHT), ReplayEvent::Undo]);
+    assert!(replay.events.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert!(replay.events.iter().all(|&(at, _)| at < Duration::from_secs(5)));
+}
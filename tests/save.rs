@@ -0,0 +1,66 @@
+#![cfg(feature = "serde")]
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use mazeme::GameState;
+
+/// Writes `json` to a file of its own in the temp directory.
+fn save_file(name: &str, json: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("mazeme-save-{}-{name}.json", std::process::id()));
+    fs::write(&path, json).unwrap();
+    path
+}
+
+/// A valid save of a 3x1 board with `extra` fields added.
+fn save_with(extra: &str) -> String {
+    format!(
+        r#"{{"board":["● ▓"],"position":{{"x":0,"y":0}},"win_position":{{"x":2,"y":0}},"time_remaining":30{extra}}}"#
+    )
+}
+
+#[test]
+fn saves_with_fog_closing_out_of_range_are_refused() {
+    let path = save_file("fog", &save_with(r#","visibility_radius":2,"fog_closing":18446744073709551615"#));
+    let err = GameState::load(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("fog_closing"));
+}
+
+#[test]
+fn saves_with_positions_off_the_board_are_refused() {
+    for (name, extra) in [
+        ("history", r#","history":[{"x":99,"y":99}]"#),
+        ("enemy", r#","enemies":[{"x":3,"y":0}]"#),
+    ] {
+        let path = save_file(name, &save_with(extra));
+        let err = GameState::load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains(&format!("saved {name} position")), "{err}");
+    }
+    let path = save_file("valid", &save_with(r#","history":[{"x":1,"y":0}]"#));
+    let state = GameState::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(state.is_ok());
+}
+
+#[test]
+fn saves_round_trip_and_show_the_trail_by_default() {
+    let mut state = GameState::new_seeded(7, 2);
+    state.show_trail = false;
+    state.moves = 4;
+    let path = save_file("round-trip", "");
+    state.save(&path).unwrap();
+    let loaded = GameState::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.board(), state.board());
+    assert_eq!((loaded.position, loaded.moves, loaded.show_trail), (state.position, 4, false));
+
+    let path = save_file("no-trail-key", &save_with(""));
+    let loaded = GameState::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(loaded.show_trail);
+}
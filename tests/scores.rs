@@ -0,0 +1,32 @@
+#![cfg(feature = "serde")]
+
+use mazeme::game_state::scores::{Leaderboard, Score};
+use mazeme::{GameSetup, MazeAlgorithm};
+
+fn setup(seed: u64) -> GameSetup {
+    GameSetup { seed, algorithm: MazeAlgorithm::Prim, width: 11, height: 11, ..GameSetup::default() }
+}
+
+#[test]
+fn only_better_scores_replace_a_record() {
+    let mut board = Leaderboard::default();
+    assert!(board.submit(setup(1), Score { time_ms: 9000, moves: 40 }));
+    assert!(!board.submit(setup(1), Score { time_ms: 9500, moves: 30 }));
+    assert!(board.submit(setup(1), Score { time_ms: 9000, moves: 38 }));
+    assert!(board.submit(setup(2), Score { time_ms: 12000, moves: 50 }));
+    assert_eq!(board.best(&setup(1)), Some(Score { time_ms: 9000, moves: 38 }));
+    assert_eq!(board.records.len(), 2);
+}
+
+#[test]
+fn leaderboard_round_trips_and_starts_empty() {
+    let dir = std::env::temp_dir().join(format!("mazeme_scores_{}", std::process::id()));
+    let path = dir.join("scores.json");
+    assert_eq!(Leaderboard::load(&path).unwrap(), Leaderboard::default());
+
+    let mut board = Leaderboard::default();
+    board.submit(setup(3), Score { time_ms: 4200, moves: 17 });
+    board.save(&path).unwrap();
+    assert_eq!(Leaderboard::load(&path).unwrap(), board);
+    std::fs::remove_dir_all(dir).unwrap();
+}
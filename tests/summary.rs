@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+
+use mazeme::game_state::summary::RunSummary;
+use mazeme::{GameSetup, MazeAlgorithm, Movement};
+
+fn setup(seed: u64) -> GameSetup {
+    GameSetup { seed, algorithm: MazeAlgorithm::Kruskal, width: 11, height: 7, ..GameSetup::default() }
+}
+
+#[test]
+fn unfinished_runs_are_summed_up_so_far() {
+    let setup = setup(4);
+    let mut state = setup.build();
+    let optimal = state.fewest_moves();
+    let path = state.solution().unwrap();
+    assert_eq!(optimal, Some(path.len() - 1));
+
+    let first = Movement::between(path[0], path[1]).unwrap();
+    state.move_position(first);
+    state.move_position(Movement::between(path[1], path[0]).unwrap());
+    let summary = RunSummary::of(&state, Some(&setup), optimal);
+    assert_eq!((summary.seed, summary.algorithm.as_deref()), (Some(4), Some("Kruskal")));
+    assert_eq!((summary.width, summary.height, summary.moves, summary.won), (11, 7, 2, false));
+    assert_eq!(summary.efficiency, Some(2.0 / optimal.unwrap() as f64));
+}
+
+#[test]
+fn optimal_counts_diagonals_and_portal_hops_as_one_move() {
+    let setup = GameSetup { portals: 2, diagonals: true, ..setup(0) };
+    let mut state = setup.build();
+    let optimal = state.fewest_moves();
+    assert!(optimal.unwrap() < state.stats.unwrap().path_length.unwrap());
+
+    while !state.victory {
+        let path = state.solution().unwrap();
+        state.move_position(Movement::between(path[0], path[1]).unwrap());
+    }
+    let summary = RunSummary::of(&state, Some(&setup), optimal);
+    assert_eq!(summary.moves, optimal.unwrap());
+    assert_eq!(summary.efficiency, Some(1.0));
+}
+
+#[test]
+fn summaries_print_as_json_lines() {
+    let state = setup(9).build();
+    let summary = RunSummary::of(&state, None, None);
+    let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+    assert_eq!(json["seed"], serde_json::Value::Null);
+    assert_eq!(json["moves"], 0);
+    assert!(!summary.to_json().contains('\n'));
+    assert!(summary.to_string().contains("size: 11x7"));
+}